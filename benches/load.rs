@@ -0,0 +1,59 @@
+//! Loading and refreshing synthetic data directories of growing size.
+//! Run with `cargo bench --bench load`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::{fs, path::PathBuf};
+
+// The app has no library target, so the loader is compiled in directly
+#[allow(dead_code)]
+#[path = "../src/data.rs"]
+mod data;
+
+/// Objectives written to every synthetic file
+const OBJECTIVES_PER_FILE: usize = 25;
+
+/// Sizes of the synthetic directories in files, put ten to a subdirectory
+const FILE_COUNTS: [usize; 3] = [10, 100, 1000];
+
+/// Writes `files` location files into a fresh directory under the temp directory
+fn synthetic_dir(files: usize) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tpgui-bench-{}-{}", std::process::id(), files));
+    let _ = fs::remove_dir_all(&dir);
+    for file in 0..files {
+        let subdir = dir.join(format!("Group {}", file / 10));
+        fs::create_dir_all(&subdir).unwrap();
+        let objectives: Vec<String> = (0..OBJECTIVES_PER_FILE)
+            .map(|i| {
+                let (map, x, y) = (file % 50, i * 10, file);
+                let pos = format!("[{x}.5, {y}.25, 10.0]");
+                format!(r#"{{"name": "Objective {i}", "map": {map}, "pos": {pos}}}"#)
+            })
+            .collect();
+        let json = format!(
+            r#"{{"name": "Location {file}", "objectives": [{}]}}"#,
+            objectives.join(", ")
+        );
+        fs::write(subdir.join(format!("Location {file}.json")), json).unwrap();
+    }
+    dir
+}
+
+fn load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load");
+    group.sample_size(20);
+    for files in FILE_COUNTS {
+        let dir = synthetic_dir(files);
+        group.bench_with_input(BenchmarkId::new("full", files), &dir, |b, dir| {
+            b.iter(|| data::DataTree::load(dir))
+        });
+        let mut tree = data::DataTree::load(&dir);
+        group.bench_with_input(BenchmarkId::new("refresh_unchanged", files), &dir, |b, dir| {
+            b.iter(|| tree.refresh(dir))
+        });
+        let _ = fs::remove_dir_all(&dir);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use std::{path::PathBuf, process};
+
+const USAGE: &str = "Usage: imgui_demo [--quiet] [--select NAME]... [--screenshot FILE]
+
+Options:
+  -q, --quiet          Don't print selected objectives to the terminal
+      --select NAME    Run the selection action for the objective named NAME after loading,
+                       as if it was clicked; can be given more than once
+      --screenshot FILE
+                       Render the window once to a PNG file without showing it, then exit
+  -h, --help           Show this message";
+
+/// Command line options
+#[derive(Default)]
+pub struct Args {
+    /// Suppress selection messages on stdout, overriding the config
+    pub quiet: bool,
+    /// Render one frame to this image file and exit instead of running interactively
+    pub screenshot: Option<PathBuf>,
+    /// Objectives to select by name once the data is loaded, in order
+    pub select: Vec<String>,
+}
+
+impl Args {
+    /// Parses the process arguments, exiting on `--help` or an unknown option
+    pub fn parse() -> Self {
+        let mut args = Args::default();
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-q" | "--quiet" => args.quiet = true,
+                "--select" => match iter.next() {
+                    Some(name) => args.select.push(name),
+                    None => {
+                        eprintln!("--select needs an objective name\n\n{}", USAGE);
+                        process::exit(2);
+                    }
+                },
+                "--screenshot" => match iter.next() {
+                    Some(path) => args.screenshot = Some(PathBuf::from(path)),
+                    None => {
+                        eprintln!("--screenshot needs a file name\n\n{}", USAGE);
+                        process::exit(2);
+                    }
+                },
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => {
+                    eprintln!("Unknown option {}\n\n{}", arg, USAGE);
+                    process::exit(2);
+                }
+            }
+        }
+        args
+    }
+}
@@ -0,0 +1,38 @@
+/// The system clipboard, opened on first use
+///
+/// The handle is kept alive because on Linux the copied text is only
+/// available while its owner exists.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Copies `text` to the system clipboard
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        let result = self.open()?.set_text(text).map_err(|e| e.to_string());
+        self.check(result)
+    }
+
+    /// Reads the text on the system clipboard
+    pub fn paste(&mut self) -> Result<String, String> {
+        let result = self.open()?.get_text().map_err(|e| e.to_string());
+        self.check(result)
+    }
+
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.inner.is_none() {
+            self.inner = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        Ok(self.inner.as_mut().expect("clipboard was just opened"))
+    }
+
+    /// Passes `result` through, dropping the handle if it failed
+    fn check<T>(&mut self, result: Result<T, String>) -> Result<T, String> {
+        if result.is_err() {
+            // The handle may be stale, e.g. after the clipboard owner went away; reopen next time
+            self.inner = None;
+        }
+        result
+    }
+}
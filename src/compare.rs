@@ -0,0 +1,89 @@
+use crate::{config::Config, data::Position};
+use imgui::{Condition, Ui};
+
+/// An objective picked for comparison, copied so it survives reloads
+struct Picked {
+    name: String,
+    pos: Option<[f32; 3]>,
+}
+
+impl Picked {
+    fn new(objective: &Position) -> Self {
+        Picked {
+            name: objective.name.clone(),
+            pos: objective.pos,
+        }
+    }
+}
+
+/// The "Compare objectives" window, which shows the distance between two objectives
+#[derive(Default)]
+pub struct CompareWindow {
+    pub open: bool,
+    first: Option<Picked>,
+    second: Option<Picked>,
+}
+
+impl CompareWindow {
+    /// Uses `objective` as the first or second side and shows the window
+    pub fn pick(&mut self, objective: &Position, second: bool) {
+        let side = if second { &mut self.second } else { &mut self.first };
+        *side = Some(Picked::new(objective));
+        self.open = true;
+    }
+
+    /// Draws the window; `layout` is when the default position and size apply
+    pub fn draw(&mut self, ui: &Ui, config: &Config, layout: Condition) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        ui.window("Compare objectives")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([320.0, 0.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                for (label, side) in [("A", &self.first), ("B", &self.second)] {
+                    match side {
+                        Some(Picked { name, pos: Some(pos) }) => {
+                            ui.text(format!("{}: {} [{}]", label, name, config.format_coords(*pos)))
+                        }
+                        Some(Picked { name, pos: None }) => {
+                            ui.text(format!("{}: {} (no position data)", label, name))
+                        }
+                        None => ui.text_disabled(format!("{}: right-click an objective", label)),
+                    }
+                }
+                ui.separator();
+
+                let first = self.first.as_ref().and_then(|picked| picked.pos);
+                let second = self.second.as_ref().and_then(|picked| picked.pos);
+                let positions = first.zip(second);
+                let _disabled = ui.begin_disabled(positions.is_none());
+                let (delta, distance) = match positions {
+                    Some((a, b)) => {
+                        let delta = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+                        let distance = delta.iter().map(|d| d * d).sum::<f32>().sqrt();
+                        // As precise as the most precise axis, so no delta shows more digits
+                        let precision = match config.axis_precision {
+                            Some(axes) => axes.into_iter().max().unwrap_or(0),
+                            None => config.coordinate_precision,
+                        };
+                        let distance = format!("{:.*}", precision, distance);
+                        (config.format_axes(delta), distance)
+                    }
+                    None => (Default::default(), String::new()),
+                };
+                for (label, delta) in config.axis_labels().iter().zip(delta) {
+                    ui.text(format!("d{}: {}", label.to_lowercase(), delta));
+                }
+                ui.text(format!("Distance: {}", distance));
+                if positions.is_none() {
+                    ui.text_disabled("Pick two objectives with positions to compare");
+                }
+            });
+        self.open = open;
+    }
+}
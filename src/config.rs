@@ -0,0 +1,754 @@
+use crate::data::{MapBounds, ObjectiveKey};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// File the user settings are persisted to
+const CONFIG_PATH: &str = "config.toml";
+
+/// Number of directories kept in File > Open recent
+const MAX_RECENT_DIRS: usize = 8;
+
+/// Smallest value accepted for `min_window_size`, below which it is raised on load
+const MIN_WINDOW_SIZE: [u32; 2] = [200, 150];
+
+/// Default `map_url` of earlier versions, saved in their config files
+const OLD_MAP_URL: &str = "https://api.guildwars2.com/v2/maps/{map}";
+
+/// Number of queries kept in the search history
+const MAX_SEARCH_HISTORY: usize = 10;
+
+/// Color scheme of the UI
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    Classic,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::Classic];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::Classic => "Classic",
+        }
+    }
+
+    /// Returns the theme after this one in `ALL`, wrapping around
+    pub fn next(self) -> Theme {
+        let index = Theme::ALL.iter().position(|&theme| theme == self).unwrap_or(0);
+        Theme::ALL[(index + 1) % Theme::ALL.len()]
+    }
+
+    /// Applies the theme's colors to the imgui style
+    pub fn apply(self, style: &mut imgui::Style) {
+        match self {
+            Theme::Dark => style.use_dark_colors(),
+            Theme::Light => style.use_light_colors(),
+            Theme::Classic => style.use_classic_colors(),
+        };
+    }
+
+    /// Background color behind the imgui windows
+    pub fn clear_color(self) -> [f32; 4] {
+        match self {
+            Theme::Dark => [0.2, 0.2, 0.2, 1.0],
+            Theme::Light => [0.85, 0.85, 0.85, 1.0],
+            Theme::Classic => [0.15, 0.15, 0.2, 1.0],
+        }
+    }
+}
+
+/// What selecting an objective does with its position
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Print the selection to stdout
+    #[default]
+    Print,
+    /// Copy the coordinates to the clipboard
+    Clipboard,
+    Both,
+}
+
+impl OutputMode {
+    pub const ALL: [OutputMode; 3] = [OutputMode::Print, OutputMode::Clipboard, OutputMode::Both];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OutputMode::Print => "Print to terminal",
+            OutputMode::Clipboard => "Copy to clipboard",
+            OutputMode::Both => "Print and copy",
+        }
+    }
+
+    pub fn prints(self) -> bool {
+        matches!(self, OutputMode::Print | OutputMode::Both)
+    }
+
+    pub fn copies(self) -> bool {
+        matches!(self, OutputMode::Clipboard | OutputMode::Both)
+    }
+}
+
+/// Click that runs the selection action for an objective
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Activation {
+    #[default]
+    Single,
+    /// A single click only marks the objective, so a stray click never teleports
+    Double,
+}
+
+impl Activation {
+    pub const ALL: [Activation; 2] = [Activation::Single, Activation::Double];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Activation::Single => "Single click",
+            Activation::Double => "Double click",
+        }
+    }
+}
+
+/// Spacing preset applied to the teleport window
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// Tight padding so more rows fit on screen
+    Compact,
+    /// Roomier padding for bigger click targets
+    #[default]
+    Comfortable,
+}
+
+impl Density {
+    pub const ALL: [Density; 2] = [Density::Compact, Density::Comfortable];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Density::Compact => "Compact",
+            Density::Comfortable => "Comfortable",
+        }
+    }
+
+    /// Returns the other preset
+    pub fn toggled(self) -> Self {
+        match self {
+            Density::Compact => Density::Comfortable,
+            Density::Comfortable => Density::Compact,
+        }
+    }
+
+    /// Frame padding style var for this preset
+    pub fn frame_padding(self) -> [f32; 2] {
+        match self {
+            Density::Compact => [4.0, 1.0],
+            Density::Comfortable => [8.0, 5.0],
+        }
+    }
+
+    /// Item spacing style var for this preset
+    pub fn item_spacing(self) -> [f32; 2] {
+        match self {
+            Density::Compact => [6.0, 2.0],
+            Density::Comfortable => [8.0, 6.0],
+        }
+    }
+
+    /// Label of the button that switches to the other preset
+    pub fn toggle_label(self) -> &'static str {
+        match self {
+            Density::Compact => "Comfortable view",
+            Density::Comfortable => "Compact view",
+        }
+    }
+}
+
+/// Order objectives are listed in within a location
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    /// As written in the data file
+    #[default]
+    FileOrder,
+    Name,
+    /// Grouped by map ID, then by name
+    MapThenName,
+    /// Most often selected first
+    MostUsed,
+    /// Highest `priority` first, then by name; objectives without one go last
+    Priority,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 5] = [
+        SortMode::FileOrder,
+        SortMode::Name,
+        SortMode::MapThenName,
+        SortMode::MostUsed,
+        SortMode::Priority,
+    ];
+
+    /// Label shown in the sort selector
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::FileOrder => "File order",
+            SortMode::Name => "Name",
+            SortMode::MapThenName => "Map, then name",
+            SortMode::MostUsed => "Most used",
+            SortMode::Priority => "Priority",
+        }
+    }
+}
+
+/// How objectives are listed in the teleport window
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewMode {
+    /// Nested by directory and location file
+    #[default]
+    Tree,
+    /// One flat, sortable table of every objective
+    Table,
+    /// Every objective as a button in one list, with its location below it
+    Flat,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 3] = [ViewMode::Tree, ViewMode::Flat, ViewMode::Table];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewMode::Tree => "Tree",
+            ViewMode::Flat => "Flat list",
+            ViewMode::Table => "Table",
+        }
+    }
+}
+
+/// How the main window is sized on launch
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    Maximized,
+    /// Borderless fullscreen on the primary monitor
+    Fullscreen,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [
+        WindowMode::Windowed,
+        WindowMode::Maximized,
+        WindowMode::Fullscreen,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Maximized => "Maximized",
+            WindowMode::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+/// Which GPU to render with on systems with two; only honored by Mesa drivers on Linux,
+/// through `DRI_PRIME`, elsewhere the OS graphics settings decide
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GpuPreference {
+    #[default]
+    Default,
+    /// The discrete GPU of a laptop
+    HighPerformance,
+    /// The integrated GPU
+    LowPower,
+}
+
+impl GpuPreference {
+    pub const ALL: [GpuPreference; 3] = [
+        GpuPreference::Default,
+        GpuPreference::HighPerformance,
+        GpuPreference::LowPower,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GpuPreference::Default => "System default",
+            GpuPreference::HighPerformance => "High performance",
+            GpuPreference::LowPower => "Low power",
+        }
+    }
+
+    /// Value of Mesa's `DRI_PRIME` that picks this GPU, `None` to leave it alone
+    pub fn dri_prime(self) -> Option<&'static str> {
+        match self {
+            GpuPreference::Default => None,
+            GpuPreference::HighPerformance => Some("1"),
+            GpuPreference::LowPower => Some("0"),
+        }
+    }
+}
+
+/// User settings, loaded from and saved to `config.toml`
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub theme: Theme,
+    /// Color of search matches, favorite stars and selection markers, as RGB
+    pub accent_color: [f32; 3],
+    /// Font size in points, before HiDPI scaling
+    pub font_size: f32,
+    /// Directory the teleport tree is loaded from
+    pub data_dir: PathBuf,
+    /// Directories opened with File > Open directory..., most recent first
+    pub recent_dirs: Vec<PathBuf>,
+    /// Recent search queries, most recent first
+    pub search_history: Vec<String>,
+    /// Start in the most recent directory instead of `data_dir` when it still exists
+    pub reopen_last_dir: bool,
+    /// Rescan the data directory every this many seconds, off when unset
+    pub auto_reload_secs: Option<u64>,
+    /// Parse each data file only when its node is first opened, once the data directory
+    /// has at least this many files; never when unset
+    pub lazy_load_files: Option<usize>,
+    /// Decimal places shown for coordinates
+    pub coordinate_precision: usize,
+    /// Decimal places for x, y and z, overriding `coordinate_precision` when set
+    pub axis_precision: Option<[usize; 3]>,
+    /// Order coordinates are shown, copied and printed in, such as `xzy`; a `-` before an
+    /// axis flips its sign. Stored positions are left as they are.
+    pub axis_order: String,
+    /// Put between the coordinates copied to the clipboard or printed on selection
+    pub coordinate_separator: String,
+    pub output_mode: OutputMode,
+    /// Never print selections to the terminal, errors are still logged
+    pub quiet: bool,
+    /// File every selection is appended to with a timestamp, off when empty
+    pub selection_log: String,
+    /// Move the selections log to `<file>.1` once it reaches this many KiB, never when unset
+    pub selection_log_max_kb: Option<u64>,
+    /// File replaced with the last selected objective as one line of JSON, off when empty
+    pub selection_file: String,
+    /// Program that opens a data file at a line, with `{file}` and `{line}` in its
+    /// arguments; empty opens files with the default app
+    pub editor: String,
+    /// Hint shown for objectives with a position but no hint, with `{x}`, `{y}`, `{z}` and
+    /// `{pos}` replaced by the coordinates; empty shows nothing
+    pub position_hint: String,
+    /// Page opened by "Open map in browser", with `{map}` replaced by the map ID
+    pub map_url: String,
+    /// Ask "Teleport to X?" before running the selection action
+    pub confirm_selection: bool,
+    pub activation: Activation,
+    pub density: Density,
+    pub sort_mode: SortMode,
+    pub view_mode: ViewMode,
+    pub window_mode: WindowMode,
+    pub gpu: GpuPreference,
+    /// Smallest inner size the window can be resized to, in logical pixels
+    pub min_window_size: [u32; 2],
+    /// Corner radius of buttons and other framed widgets in pixels
+    pub frame_rounding: f32,
+    /// Border width of buttons and other framed widgets in pixels, 0 for flat widgets
+    pub frame_border: f32,
+    /// Draw a small icon before directories, locations and objectives in the tree
+    pub tree_icons: bool,
+    /// Slide tree nodes open and closed over a few frames instead of at once
+    pub animate_tree: bool,
+    /// List objectives as small buttons that wrap onto as few lines as fit
+    pub compact_objectives: bool,
+    /// Columns the objectives of a location are laid out in, 1 for a single list
+    pub objective_columns: usize,
+    /// Make the whole row of an objective clickable instead of just its button
+    pub full_row_select: bool,
+    /// Height of full-row objective rows in pixels, 0 for the default height
+    pub row_height: f32,
+    /// Open the top-level directories and locations after loading
+    pub expand_top_level: bool,
+    /// Most search results listed at once, 0 for no limit
+    pub max_search_results: usize,
+    /// Keep only one location open at a time, closing the others when one is opened
+    pub accordion: bool,
+    /// Show FPS and frame time in a corner of the screen
+    pub show_fps: bool,
+    /// Draw frames all the time instead of only after input and while something animates
+    pub continuous_redraw: bool,
+    /// Chord that switches to the next theme, such as `F2` or `Ctrl+T`; none when empty
+    pub theme_key: String,
+    /// Chord that shows or hides the errors panel, such as `F4` or `Ctrl+E`; none when empty
+    pub errors_key: String,
+    /// Show the source file's full path when hovering a location
+    pub path_tooltips: bool,
+    /// Show how often each objective was selected next to its button
+    pub show_usage_counts: bool,
+    /// Port of the localhost HTTP API, used with the `http-server` feature
+    pub http_port: u16,
+    /// Keep the positions and sizes of the app's windows in `imgui.ini`
+    pub save_layout: bool,
+    /// Show a message box when the app crashes, in addition to writing the crash log
+    pub crash_dialog: bool,
+    /// Objective selected most recently, marked in the tree
+    pub last_selected: Option<ObjectiveKey>,
+    /// Coordinate ranges per map; objectives outside their map's range are reported
+    pub map_bounds: Vec<MapBounds>,
+    /// Also report identical positions in different files, not only within one file
+    pub duplicates_across_files: bool,
+    /// Regex matched against each data file's path below the data directory, with `/`
+    /// separators, whose first group is the map its objectives belong on, such as
+    /// `maps/(\d+)\.json`; objectives on another map are reported. Off when empty.
+    pub map_path_pattern: String,
+    /// Start of the previous session in seconds since the Unix epoch
+    pub last_launch: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            theme: Theme::default(),
+            accent_color: [1.0, 0.85, 0.3],
+            font_size: 13.0,
+            data_dir: PathBuf::from("data"),
+            recent_dirs: Vec::new(),
+            search_history: Vec::new(),
+            reopen_last_dir: true,
+            auto_reload_secs: None,
+            lazy_load_files: Some(2000),
+            coordinate_precision: 2,
+            axis_precision: None,
+            axis_order: "xyz".to_string(),
+            coordinate_separator: ", ".to_string(),
+            output_mode: OutputMode::default(),
+            quiet: false,
+            selection_log: String::new(),
+            selection_log_max_kb: None,
+            selection_file: String::new(),
+            confirm_selection: false,
+            activation: Activation::Single,
+            // The wiki page of the map, found by its ID
+            map_url: concat!(
+                "https://wiki.guildwars2.com/wiki/",
+                "Special:SearchByProperty/Has-20map-20id/{map}"
+            )
+            .to_string(),
+            editor: String::new(),
+            position_hint: String::new(),
+            density: Density::default(),
+            sort_mode: SortMode::default(),
+            view_mode: ViewMode::default(),
+            window_mode: WindowMode::default(),
+            gpu: GpuPreference::default(),
+            min_window_size: [480, 360],
+            frame_rounding: 0.0,
+            frame_border: 0.0,
+            tree_icons: true,
+            animate_tree: false,
+            objective_columns: 1,
+            compact_objectives: false,
+            full_row_select: false,
+            row_height: 0.0,
+            show_usage_counts: false,
+            path_tooltips: true,
+            show_fps: false,
+            continuous_redraw: false,
+            theme_key: "F2".to_string(),
+            errors_key: "F4".to_string(),
+            expand_top_level: false,
+            accordion: false,
+            max_search_results: 100,
+            save_layout: false,
+            crash_dialog: true,
+            http_port: 8732,
+            last_selected: None,
+            map_bounds: Vec::new(),
+            duplicates_across_files: false,
+            map_path_pattern: String::new(),
+            last_launch: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to defaults if it is missing or invalid
+    pub fn load() -> Self {
+        let mut config = match fs::read_to_string(CONFIG_PATH) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Error parsing {}: {}", CONFIG_PATH, e);
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        };
+        config.clamp();
+        config
+    }
+
+    /// Reads the config file, reporting errors instead of falling back to defaults
+    pub fn read() -> Result<Self, String> {
+        let content = fs::read_to_string(CONFIG_PATH)
+            .map_err(|e| format!("Error reading {}: {}", CONFIG_PATH, e))?;
+        let mut config: Config = toml::from_str(&content)
+            .map_err(|e| format!("Error parsing {}: {}", CONFIG_PATH, e))?;
+        config.clamp();
+        Ok(config)
+    }
+
+    /// Raises sizes that would make the UI unusable, and replaces the former `map_url`
+    /// default, which browsers show as JSON rather than a map
+    pub fn clamp(&mut self) {
+        for (size, min) in self.min_window_size.iter_mut().zip(MIN_WINDOW_SIZE) {
+            *size = (*size).max(min);
+        }
+        if self.map_url == OLD_MAP_URL {
+            self.map_url = Config::default().map_url;
+        }
+    }
+
+    /// Names the settings that differ from `other` but only take effect on the next launch
+    pub fn restart_only_changes(&self, other: &Config) -> Vec<&'static str> {
+        let mut changes = Vec::new();
+        if self.window_mode != other.window_mode {
+            changes.push("window mode");
+        }
+        if self.gpu != other.gpu {
+            changes.push("GPU");
+        }
+        if self.crash_dialog != other.crash_dialog {
+            changes.push("crash dialog");
+        }
+        if self.http_port != other.http_port {
+            changes.push("HTTP port");
+        }
+        changes
+    }
+
+    /// Returns true if the data directory has to be loaded again to apply this config instead
+    /// of `other`, because they load or check it differently
+    pub fn needs_reload(&self, other: &Config) -> bool {
+        self.map_bounds != other.map_bounds
+            || self.duplicates_across_files != other.duplicates_across_files
+            || self.lazy_load_files != other.lazy_load_files
+            || self.map_path_pattern != other.map_path_pattern
+    }
+
+    /// Returns this config with the settings that differ between `base` and `edited` taken
+    /// from `edited`, so edits made to a copy of `base` don't revert later changes to this one
+    pub fn with_changes(&self, base: &Config, edited: &Config) -> Config {
+        let fields = |config: &Config| match serde_json::to_value(config) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => serde_json::Map::new(),
+        };
+        let base = fields(base);
+        let mut merged = fields(self);
+        for (key, value) in fields(edited) {
+            if base.get(&key) != Some(&value) {
+                merged.insert(key, value);
+            }
+        }
+        serde_json::from_value(merged.into()).unwrap_or_else(|e| {
+            eprintln!("Error applying settings: {}", e);
+            self.clone()
+        })
+    }
+
+    /// Returns the directory to load on launch
+    pub fn startup_data_dir(&self) -> PathBuf {
+        match self.recent_dirs.first() {
+            Some(dir) if self.reopen_last_dir && dir.is_dir() => dir.clone(),
+            _ => self.data_dir.clone(),
+        }
+    }
+
+    /// Moves `dir` to the front of the recent directories
+    pub fn add_recent_dir(&mut self, dir: PathBuf) {
+        self.recent_dirs.retain(|recent| *recent != dir);
+        self.recent_dirs.insert(0, dir);
+        self.recent_dirs.truncate(MAX_RECENT_DIRS);
+    }
+
+    /// Moves `query` to the front of the search history
+    pub fn add_search(&mut self, query: &str) {
+        self.search_history.retain(|recent| recent != query);
+        self.search_history.insert(0, query.to_string());
+        self.search_history.truncate(MAX_SEARCH_HISTORY);
+    }
+
+    /// Drops recent directories that no longer exist, returning true if any were removed
+    pub fn prune_recent_dirs(&mut self) -> bool {
+        let count = self.recent_dirs.len();
+        self.recent_dirs.retain(|dir| dir.is_dir());
+        self.recent_dirs.len() != count
+    }
+
+    /// Formats a position as `x, y, z` with the configured precision
+    pub fn format_coords(&self, pos: [f32; 3]) -> String {
+        self.format_axes(pos).join(", ")
+    }
+
+    /// Returns `accent_color` as an opaque RGBA color
+    pub fn accent(&self) -> [f32; 4] {
+        let [r, g, b] = self.accent_color;
+        [r, g, b, 1.0]
+    }
+
+    /// Formats a position for the clipboard and terminal, using `coordinate_separator`
+    pub fn format_output(&self, pos: [f32; 3]) -> String {
+        self.format_axes(pos).join(&self.coordinate_separator)
+    }
+
+    /// Formats each coordinate of a position with its axis' precision, in `axis_order`
+    pub fn format_axes(&self, pos: [f32; 3]) -> [String; 3] {
+        let precision = self.axis_precision.unwrap_or([self.coordinate_precision; 3]);
+        let order = parse_axis_order(&self.axis_order).unwrap_or(IDENTITY_ORDER);
+        order.map(|(axis, flip)| {
+            let value = if flip { -pos[axis] } else { pos[axis] };
+            format!("{:.*}", precision[axis], value)
+        })
+    }
+
+    /// Returns the names of the displayed coordinates in `axis_order`, such as "X", "-Z", "Y"
+    pub fn axis_labels(&self) -> [String; 3] {
+        let order = parse_axis_order(&self.axis_order).unwrap_or(IDENTITY_ORDER);
+        order.map(|(axis, flip)| {
+            let sign = if flip { "-" } else { "" };
+            format!("{}{}", sign, ["X", "Y", "Z"][axis])
+        })
+    }
+
+    /// Returns a position's coordinates in `axis_order`, as they are displayed
+    pub fn output_pos(&self, pos: [f32; 3]) -> [f32; 3] {
+        let order = parse_axis_order(&self.axis_order).unwrap_or(IDENTITY_ORDER);
+        order.map(|(axis, flip)| if flip { -pos[axis] } else { pos[axis] })
+    }
+
+    /// Fills `position_hint` in for `pos`, or returns `None` if it is empty
+    pub fn position_hint(&self, pos: [f32; 3]) -> Option<String> {
+        if self.position_hint.is_empty() {
+            return None;
+        }
+        let [x, y, z] = self.format_axes(pos);
+        Some(
+            self.position_hint
+                .replace("{pos}", &self.format_coords(pos))
+                .replace("{x}", &x)
+                .replace("{y}", &y)
+                .replace("{z}", &z),
+        )
+    }
+
+    /// Writes the config file
+    pub fn save(&self) {
+        let result = toml::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(CONFIG_PATH, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Error saving {}: {}", CONFIG_PATH, e);
+        }
+    }
+}
+
+/// Stored axis and whether it is negated, for each output coordinate
+type AxisOrder = [(usize, bool); 3];
+
+const IDENTITY_ORDER: AxisOrder = [(0, false), (1, false), (2, false)];
+
+/// Parses an axis order such as `xzy` or `x, -z, y`, which must name each axis once
+pub fn parse_axis_order(text: &str) -> Option<AxisOrder> {
+    let mut order = Vec::new();
+    let mut flip = false;
+    for c in text.chars() {
+        match c.to_ascii_lowercase() {
+            '-' if !flip => flip = true,
+            c @ ('x' | 'y' | 'z') => {
+                let axis = (c as u8 - b'x') as usize;
+                if order.iter().any(|&(used, _)| used == axis) {
+                    return None;
+                }
+                order.push((axis, flip));
+                flip = false;
+            }
+            c if (c == ',' || c.is_whitespace()) && !flip => {}
+            _ => return None,
+        }
+    }
+    order.try_into().ok()
+}
+
+/// Notices edits to the config file made while the app runs
+pub struct ConfigWatcher {
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    /// How often the file's modification time is checked
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new() -> Self {
+        ConfigWatcher {
+            modified: Self::modified(),
+            last_check: Instant::now(),
+        }
+    }
+
+    fn modified() -> Option<SystemTime> {
+        fs::metadata(CONFIG_PATH).and_then(|m| m.modified()).ok()
+    }
+
+    /// Returns true once after each modification of the existing file
+    pub fn changed(&mut self) -> bool {
+        if self.last_check.elapsed() < Self::INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        let modified = Self::modified();
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_axis_orders() {
+        let cases: [(&str, Option<AxisOrder>); 9] = [
+            ("xyz", Some(IDENTITY_ORDER)),
+            ("xzy", Some([(0, false), (2, false), (1, false)])),
+            ("x, -z, y", Some([(0, false), (2, true), (1, false)])),
+            ("-Y X -Z", Some([(1, true), (0, false), (2, true)])),
+            ("xxy", None),
+            ("xy", None),
+            ("-", None),
+            ("x--yz", None),
+            ("x- yz", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_axis_order(text), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn labels_axes_in_display_order() {
+        let config = Config {
+            axis_order: "x, -z, y".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.axis_labels(), ["X", "-Z", "Y"]);
+        assert_eq!(config.output_pos([1.0, 2.0, 3.0]), [1.0, -3.0, 2.0]);
+    }
+}
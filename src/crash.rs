@@ -0,0 +1,50 @@
+use std::{
+    backtrace::Backtrace,
+    fs::OpenOptions,
+    io::Write,
+    panic,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// File panic reports are appended to
+const CRASH_LOG_PATH: &str = "crash.log";
+
+/// Installs a panic hook that appends the panic and a backtrace to the crash log,
+/// optionally telling the user about it with a native message box
+pub fn install_panic_hook(show_dialog: bool) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let report = format!(
+            "[{}] {}\n{}\n",
+            timestamp,
+            info,
+            Backtrace::force_capture()
+        );
+
+        let logged = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(CRASH_LOG_PATH)
+            .and_then(|mut file| file.write_all(report.as_bytes()));
+        if let Err(e) = &logged {
+            eprintln!("Error writing {}: {}", CRASH_LOG_PATH, e);
+        }
+
+        default_hook(info);
+
+        if show_dialog {
+            let details = match logged {
+                Ok(()) => format!("Details were written to {}.", CRASH_LOG_PATH),
+                Err(_) => String::from("The crash log could not be written."),
+            };
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("GW2 Teleport crashed")
+                .set_description(format!("{}\n\n{}", info, details))
+                .show();
+        }
+    }));
+}
@@ -0,0 +1,170 @@
+use serde_json::{json, Map, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Result of a CSV import
+pub struct CsvReport {
+    /// The location file that was written
+    pub path: PathBuf,
+    pub imported: usize,
+    /// Line number and reason of every row that was skipped
+    pub skipped: Vec<(u64, String)>,
+}
+
+/// Parses an optional number column
+fn parse_number<T: std::str::FromStr>(
+    value: Option<&str>,
+    column: &str,
+) -> Result<Option<T>, String> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("{} is not a number: {:?}", column, value)),
+        None => Ok(None),
+    }
+}
+
+/// Converts one CSV record into an objective
+fn parse_row(record: &csv::StringRecord, columns: &[Option<usize>; 6]) -> Result<Value, String> {
+    let field = |index: usize| columns[index].and_then(|column| record.get(column));
+    let name = field(0).map(str::trim).unwrap_or_default();
+    if name.is_empty() {
+        return Err("name is empty".to_string());
+    }
+
+    let mut objective = Map::new();
+    objective.insert("name".to_string(), json!(name));
+    if let Some(hint) = field(5).map(str::trim).filter(|hint| !hint.is_empty()) {
+        objective.insert("hint".to_string(), json!(hint));
+    }
+    if let Some(map) = parse_number::<i32>(field(1), "map")? {
+        objective.insert("map".to_string(), json!(map));
+    }
+    // JSON has no NaN or infinity; `json!` would write them as null
+    let axis = |index: usize, column: &str| match parse_number::<f32>(field(index), column)? {
+        Some(value) if !value.is_finite() => Err(format!("{} is not a finite number", column)),
+        value => Ok(value),
+    };
+    let axes = [axis(2, "x")?, axis(3, "y")?, axis(4, "z")?];
+    match axes {
+        [Some(x), Some(y), Some(z)] => {
+            objective.insert("pos".to_string(), json!([x, y, z]));
+        }
+        [None, None, None] => {}
+        _ => return Err("x, y and z must all be set or all be empty".to_string()),
+    }
+    Ok(Value::Object(objective))
+}
+
+/// Converts a CSV file with a `name, map, x, y, z, hint` header into a location file in
+/// `data_dir` named after the CSV file; malformed rows are skipped and reported
+pub fn import_csv(csv_path: &Path, data_dir: &Path) -> Result<CsvReport, String> {
+    let stem = csv_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Imported".to_string());
+    let path = data_dir.join(format!("{}.json", stem));
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(csv_path)
+        .map_err(|e| format!("Could not read {}: {}", csv_path.display(), e))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Could not read {}: {}", csv_path.display(), e))?
+        .clone();
+    let columns = ["name", "map", "x", "y", "z", "hint"].map(|name| {
+        headers
+            .iter()
+            .position(|header| header.trim().eq_ignore_ascii_case(name))
+    });
+    if columns[0].is_none() {
+        return Err(format!("{} has no \"name\" column", csv_path.display()));
+    }
+
+    let mut objectives = Vec::new();
+    let mut skipped = Vec::new();
+    for record in reader.records() {
+        match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |position| position.line());
+                match parse_row(&record, &columns) {
+                    Ok(objective) => objectives.push(objective),
+                    Err(e) => skipped.push((line, e)),
+                }
+            }
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line());
+                skipped.push((line, e.to_string()));
+            }
+        }
+    }
+
+    let data = json!({ "name": stem, "objectives": objectives });
+    let content = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+    fs::create_dir_all(data_dir)
+        .and_then(|_| fs::write(&path, content))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    Ok(CsvReport {
+        path,
+        imported: objectives.len(),
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::TempDir;
+
+    /// Parses `row` with the columns of a `name,map,x,y,z,hint` header
+    fn parse(row: &[&str]) -> Result<Value, String> {
+        let columns = [Some(0), Some(1), Some(2), Some(3), Some(4), Some(5)];
+        parse_row(&csv::StringRecord::from(row.to_vec()), &columns)
+    }
+
+    #[test]
+    fn parses_valid_row() {
+        let objective = parse(&["Vista", "15", "1", "2.5", "-3", " Climb "]).unwrap();
+        let expected =
+            json!({ "name": "Vista", "hint": "Climb", "map": 15, "pos": [1.0, 2.5, -3.0] });
+        assert_eq!(objective, expected);
+        assert_eq!(parse(&["Bare", "", "", "", "", ""]).unwrap(), json!({ "name": "Bare" }));
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        let error = parse(&["Partial", "15", "1", "", "3", ""]).unwrap_err();
+        assert_eq!(error, "x, y and z must all be set or all be empty");
+        let error = parse(&["Map", "Queensdale", "", "", "", ""]).unwrap_err();
+        assert_eq!(error, "map is not a number: \"Queensdale\"");
+        let error = parse(&["NaN", "15", "nan", "2", "3", ""]).unwrap_err();
+        assert_eq!(error, "x is not a finite number");
+        let error = parse(&["Inf", "15", "1", "2", "inf", ""]).unwrap_err();
+        assert_eq!(error, "z is not a finite number");
+        assert_eq!(parse(&[" ", "15", "", "", "", ""]).unwrap_err(), "name is empty");
+    }
+
+    #[test]
+    fn imports_csv_and_reports_skipped_lines() {
+        let dir = TempDir::new("csv-import");
+        let csv_path = dir.join("Route.csv");
+        let csv = "name,map,x,y,z,hint\nA,15,1,2,3,\n,15,,,,\nB,15,,,,\nC,15,infinity,2,3,\n";
+        fs::write(&csv_path, csv).unwrap();
+
+        let report = import_csv(&csv_path, &dir.join("data")).unwrap();
+        assert_eq!(report.imported, 2);
+        let lines: Vec<_> = report.skipped.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, [3, 5]);
+        let data: Value = serde_json::from_str(&fs::read_to_string(&report.path).unwrap()).unwrap();
+        assert_eq!(data["name"], "Route");
+        assert_eq!(data["objectives"][0]["pos"], json!([1.0, 2.0, 3.0]));
+        assert!(import_csv(&csv_path, &dir.join("data")).is_err());
+    }
+}
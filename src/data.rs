@@ -0,0 +1,1795 @@
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// File name used by the "Create example file" button
+const EXAMPLE_FILE_NAME: &str = "Example.json";
+
+/// Waits before each refresh that re-reads files whose JSON ended early, as editors that
+/// are still saving leave them truncated for a moment
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_millis(25), Duration::from_millis(100)];
+
+/// Label substituted for objectives whose name is blank
+const UNNAMED: &str = "(unnamed)";
+
+/// Sample data file; JSON has no comments, so the hints explain each field
+const EXAMPLE_FILE: &str = r##"{
+  "name": "Example Location",
+  "color": "#7FB3FF",
+  "objectives": [
+    {
+      "name": "Objective with a position",
+      "hint": "'name' is the button label, 'hint' is shown next to it",
+      "map": 15,
+      "pos": [
+        -1234.5,
+        678.9,
+        42.0
+      ]
+    },
+    {
+      "name": "Objective without a position",
+      "hint": "'map' and 'pos' are optional; 'pos' is [x, y, z]"
+    },
+    {
+      "name": "Waypoint with a chat code",
+      "hint": "'chat_code' adds a [&] button that copies it for pasting in game",
+      "chat_code": "[&BH4BAAA=]"
+    },
+    {
+      "name": "Objective with aliases",
+      "hint": "'aliases' are extra names the search finds but never shows; try \"nickname\"",
+      "aliases": [
+        "nickname"
+      ]
+    },
+    {
+      "name": "Objective without a hint"
+    },
+    {
+      "name": "Hidden objective",
+      "hint": "'hidden' objectives only show with \"Show hidden objectives\"",
+      "hidden": true
+    }
+  ]
+}
+"##;
+
+/// Represents a position or objective in the game world; serializes to the same
+/// JSON it is read from, leaving out unset fields
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Position {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// Other names the search matches, such as abbreviations; never displayed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pos: Option<[f32; 3]>,
+    /// Waypoints of a route leading to `pos`, in the order they are passed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<[f32; 3]>,
+    /// Only shown when "Show hidden objectives" is checked
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hidden: bool,
+    /// Higher priorities are listed first when sorting by priority, unset ones last
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+    /// In-game chat link such as `[&BH4BAAA=]`, used to teleport to waypoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_code: Option<String>,
+    /// File and line the objective was read from, set on load
+    #[serde(skip)]
+    pub source: Option<SourceLine>,
+    /// Image shown before the name, written relative to the data file and resolved on load;
+    /// not serialized since the resolved path only makes sense on this machine
+    #[serde(default, skip_serializing)]
+    pub icon: Option<PathBuf>,
+}
+
+/// A line in a data file
+#[derive(Clone)]
+pub struct SourceLine {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// Position in the file's `objectives` array
+    pub index: usize,
+}
+
+/// Deserializes `null` as an empty list
+fn null_as_empty<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<Vec<T>>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+/// Used to leave `hidden: false` out of serialized objectives
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Position {
+    /// Returns the objective as pretty-printed JSON, as it would appear in a data file
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Returns the key used to remember this objective across reloads
+    pub fn key(&self) -> ObjectiveKey {
+        ObjectiveKey {
+            name: self.name.clone(),
+            map: self.map,
+        }
+    }
+
+    /// Returns the straight-line distance from `pos` to `point`, `None` without a position
+    pub fn distance_to(&self, point: [f32; 3]) -> Option<f32> {
+        let pos = self.pos?;
+        let squared: f32 = pos.iter().zip(point).map(|(a, b)| (a - b) * (a - b)).sum();
+        Some(squared.sqrt())
+    }
+
+    /// Orders by priority, highest first, then by name; objectives without one go last
+    pub fn cmp_priority(&self, other: &Position) -> std::cmp::Ordering {
+        let key = |o: &Position| (o.priority.is_none(), std::cmp::Reverse(o.priority));
+        key(self).cmp(&key(other)).then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+/// Reads x, y and z from text such as `[1.5, -2, 3]` or `1.5 -2 3`, whatever separates them
+pub fn parse_coordinates(text: &str) -> Option<[f32; 3]> {
+    let mut numbers = text
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .filter(|part| !part.is_empty())
+        .map(str::parse::<f32>);
+    let coords = [numbers.next()?.ok()?, numbers.next()?.ok()?, numbers.next()?.ok()?];
+    numbers.next().is_none().then_some(coords)
+}
+
+/// Identifies an objective by name and map, used for persisted per-objective state
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectiveKey {
+    pub name: String,
+    pub map: Option<i32>,
+}
+
+/// Represents a collection of positions/objectives
+#[derive(Serialize, Deserialize)]
+pub struct LocationData {
+    pub name: String,
+    /// Missing or `null` loads as empty
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub objectives: Vec<Position>,
+    /// Tint of the location's tree node as `#RRGGBB`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Files whose objectives are appended to this one, relative to this file;
+    /// emptied once they are merged on load
+    #[serde(default, alias = "$include", skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+}
+
+impl LocationData {
+    /// Returns the parsed `color`, or `None` if it is unset or invalid
+    pub fn color(&self) -> Option<[f32; 4]> {
+        self.color.as_deref().and_then(parse_hex_color)
+    }
+}
+
+/// Name of the optional file in a directory that sets how its tree node looks
+pub const DIRECTORY_META_FILE: &str = "_meta.json";
+
+/// Contents of a directory's `_meta.json`
+#[derive(Deserialize, Default)]
+pub struct DirectoryMeta {
+    /// Shown instead of the directory name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Directories with a higher priority are listed first, 0 when unset
+    #[serde(default)]
+    pub priority: i32,
+    /// Tint of the directory's tree node as `#RRGGBB`
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl DirectoryMeta {
+    /// Returns the parsed `color`, or `None` if it is unset or invalid
+    pub fn color(&self) -> Option<[f32; 4]> {
+        self.color.as_deref().and_then(parse_hex_color)
+    }
+}
+
+/// Parses `#RRGGBB` into an opaque RGBA color
+fn parse_hex_color(hex: &str) -> Option<[f32; 4]> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 || !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some([
+        channel(0)? as f32 / 255.0,
+        channel(2)? as f32 / 255.0,
+        channel(4)? as f32 / 255.0,
+        1.0,
+    ])
+}
+
+/// A parsed location file
+pub struct Location {
+    /// Path of the source file
+    pub path: PathBuf,
+    /// Modification time of the source file, if the platform reports one
+    pub modified: Option<SystemTime>,
+    pub data: LocationData,
+}
+
+/// A node of the loaded data directory
+pub enum Node {
+    Directory {
+        /// Display name, from the directory's `_meta.json` or else the directory name
+        name: String,
+        path: PathBuf,
+        meta: DirectoryMeta,
+        children: Vec<Node>,
+    },
+    Location(Location),
+    /// A data file of a lazily loaded tree that has not been opened yet
+    Pending {
+        /// The file name, until the file is parsed
+        name: String,
+        path: PathBuf,
+    },
+}
+
+impl Node {
+    /// Returns true if an objective with `key` is in this node or below it
+    pub fn contains(&self, key: &ObjectiveKey) -> bool {
+        match self {
+            Node::Directory { children, .. } => children.iter().any(|child| child.contains(key)),
+            Node::Location(location) => {
+                location.data.objectives.iter().any(|objective| objective.key() == *key)
+            }
+            Node::Pending { .. } => false,
+        }
+    }
+
+    /// Returns every loaded location in this node or below it, in tree order
+    pub fn locations(&self) -> Vec<&Location> {
+        match self {
+            Node::Directory { children, .. } => children.iter().flat_map(Node::locations).collect(),
+            Node::Location(location) => vec![location],
+            Node::Pending { .. } => Vec::new(),
+        }
+    }
+}
+
+/// An objective whose `pos` is not three finite numbers
+#[derive(Clone)]
+pub struct PosIssue {
+    /// Index of the objective in the file's `objectives` array
+    pub index: usize,
+    pub name: String,
+    /// The offending `pos` value as written
+    pub pos: serde_json::Value,
+    /// Line the objective starts on
+    pub line: Option<usize>,
+}
+
+/// A file that could not be loaded, or loaded with problems
+#[derive(Clone)]
+pub struct LoadError {
+    pub path: PathBuf,
+    pub message: String,
+    /// Invalid positions found in the file, which can be fixed automatically
+    pub pos_issues: Vec<PosIssue>,
+    /// Line the problem was found on, if known
+    pub line: Option<usize>,
+    /// Set when the file's JSON ended early, as while an editor is still saving it
+    pub truncated: bool,
+}
+
+impl LoadError {
+    /// A problem without automatic fixes
+    fn new(path: &Path, message: impl Into<String>) -> Self {
+        LoadError {
+            path: path.to_path_buf(),
+            message: message.into(),
+            pos_issues: Vec::new(),
+            line: None,
+            truncated: false,
+        }
+    }
+}
+
+/// Counts describing how well the data directory loaded
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LoadSummary {
+    /// JSON files found below the data directory
+    pub files: usize,
+    /// Files that parsed into a location
+    pub parsed: usize,
+    /// Files left to be parsed when their node is first opened
+    pub pending: usize,
+    pub objectives: usize,
+    /// Objectives without a `pos`
+    pub missing_pos: usize,
+}
+
+impl fmt::Display for LoadSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} files scanned, {} parsed OK, {} failed, {} objectives, {} missing positions",
+            self.files,
+            self.parsed,
+            self.files - self.parsed - self.pending,
+            self.objectives,
+            self.missing_pos
+        )?;
+        if self.pending > 0 {
+            write!(f, ", {} not loaded yet", self.pending)?;
+        }
+        Ok(())
+    }
+}
+
+/// Known coordinate range of a map, used to flag positions copied from another map
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MapBounds {
+    pub map: i32,
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl MapBounds {
+    /// Returns true if every axis of `pos` lies within the bounds, inclusive
+    fn contains(&self, pos: [f32; 3]) -> bool {
+        (0..3).all(|axis| self.min[axis] <= pos[axis] && pos[axis] <= self.max[axis])
+    }
+}
+
+/// Formats a position as `[x, y, z]` for error messages
+fn format_pos(pos: [f32; 3]) -> String {
+    format!("[{}, {}, {}]", pos[0], pos[1], pos[2])
+}
+
+/// Objectives that differ between two data directories, matched by name and map and sorted
+/// by name
+#[derive(Default)]
+pub struct DataDiff {
+    /// Only in the other directory
+    pub added: Vec<Position>,
+    /// Only in the base directory
+    pub removed: Vec<Position>,
+    /// In both with another position or hint, as (base, other)
+    pub changed: Vec<(Position, Position)>,
+}
+
+/// The data directory as loaded from disk
+#[derive(Default)]
+pub struct DataTree {
+    pub nodes: Vec<Node>,
+    pub errors: Vec<LoadError>,
+    pub summary: LoadSummary,
+    /// What loading each data file produced, by path, to skip unchanged files on refresh
+    records: HashMap<PathBuf, FileRecord>,
+    /// Errors before this index come from loading, the rest from the position checks
+    checked: usize,
+    /// Files are parsed when their node is first opened instead of up front
+    lazy: bool,
+    /// When to refresh again for files that were truncated, and how many retries were made
+    retry: Option<(Instant, usize)>,
+}
+
+/// Modification time and size of a file, compared to detect edits
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Reads the stamp of `path`, `None` if it cannot be read
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// What loading one data file produced, besides its location
+struct FileRecord {
+    /// Stamps of the file and of every file it includes, taken when it was loaded
+    stamps: Vec<(PathBuf, Stamp)>,
+    /// Problems reported while loading it
+    errors: Vec<LoadError>,
+}
+
+/// Data files re-read by `DataTree::refresh`
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct RefreshSummary {
+    pub changed: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl RefreshSummary {
+    /// Returns true if no file changed
+    pub fn is_empty(&self) -> bool {
+        *self == RefreshSummary::default()
+    }
+}
+
+impl fmt::Display for RefreshSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} changed, {} added, {} removed files",
+            self.changed, self.added, self.removed
+        )
+    }
+}
+
+/// State threaded through a directory scan
+#[derive(Default)]
+struct Scan {
+    errors: Vec<LoadError>,
+    files: usize,
+    records: HashMap<PathBuf, FileRecord>,
+    /// Locations and records of the previous scan, taken over while their files are unchanged
+    previous_locations: HashMap<PathBuf, Location>,
+    previous_records: HashMap<PathBuf, FileRecord>,
+    refresh: RefreshSummary,
+    /// Leave files not loaded before as `Node::Pending`
+    lazy: bool,
+}
+
+impl Scan {
+    /// Loads the data file at `path`, reusing the previous scan's result if neither it nor
+    /// the files it includes changed since
+    fn load(&mut self, path: PathBuf) -> Option<Location> {
+        self.files += 1;
+        // Truncated files are read again even if unchanged, as editors may keep the stamp
+        let unchanged = self.previous_records.get(&path).is_some_and(|record| {
+            record.stamps.iter().all(|(path, previous)| stamp(path) == *previous)
+                && !record.errors.iter().any(|error| error.truncated)
+        });
+        let location = self.previous_locations.remove(&path);
+        match self.previous_records.remove(&path) {
+            Some(record) if unchanged => {
+                self.errors.extend(record.errors.iter().cloned());
+                self.records.insert(path, record);
+                return location;
+            }
+            Some(_) => self.refresh.changed += 1,
+            None => self.refresh.added += 1,
+        }
+
+        let own_stamp = stamp(&path);
+        let start = self.errors.len();
+        let mut included = Vec::new();
+        let location = load_location(path.clone(), &mut self.errors, &mut included);
+        let mut stamps = vec![(path.clone(), own_stamp)];
+        stamps.extend(included.into_iter().map(|path| {
+            let stamp = stamp(&path);
+            (path, stamp)
+        }));
+        let errors = self.errors[start..].to_vec();
+        self.records.insert(path, FileRecord { stamps, errors });
+        location
+    }
+
+    /// Reads a directory's `_meta.json`, counting edits to it like edits to data files
+    fn load_meta(&mut self, path: PathBuf) -> DirectoryMeta {
+        let own_stamp = stamp(&path);
+        match self.previous_records.remove(&path) {
+            Some(record) if record.stamps.iter().all(|(_, previous)| *previous == own_stamp) => {}
+            Some(_) => self.refresh.changed += 1,
+            None => self.refresh.added += 1,
+        }
+        let start = self.errors.len();
+        let meta = read_directory_meta(&path, &mut self.errors);
+        let errors = self.errors[start..].to_vec();
+        let stamps = vec![(path.clone(), own_stamp)];
+        self.records.insert(path, FileRecord { stamps, errors });
+        meta
+    }
+
+    /// Turns the scanned nodes into a tree with its summary
+    fn finish(self, nodes: Vec<Node>) -> (DataTree, RefreshSummary) {
+        let mut tree = DataTree {
+            nodes,
+            checked: self.errors.len(),
+            errors: self.errors,
+            summary: LoadSummary::default(),
+            records: self.records,
+            lazy: self.lazy,
+            retry: None,
+        };
+        tree.count(self.files);
+        let mut refresh = self.refresh;
+        refresh.removed = self.previous_records.len();
+        (tree, refresh)
+    }
+}
+
+/// Moves every location out of `nodes` into `locations`, by path
+fn take_locations(nodes: Vec<Node>, locations: &mut HashMap<PathBuf, Location>) {
+    for node in nodes {
+        match node {
+            Node::Directory { children, .. } => take_locations(children, locations),
+            Node::Location(location) => {
+                locations.insert(location.path.clone(), location);
+            }
+            Node::Pending { .. } => {}
+        }
+    }
+}
+
+impl DataTree {
+    /// Loads every location file below `dir`
+    pub fn load(dir: &Path) -> Self {
+        let mut scan = Scan::default();
+        let nodes = load_directory(dir, &mut scan);
+        let mut tree = scan.finish(nodes).0;
+        tree.schedule_retry(0);
+        tree
+    }
+
+    /// Lists the files below `dir` without parsing them; each is loaded by `load_pending`
+    /// when its node is first opened
+    pub fn load_lazy(dir: &Path) -> Self {
+        let mut scan = Scan {
+            lazy: true,
+            ..Scan::default()
+        };
+        let nodes = load_directory(dir, &mut scan);
+        let mut tree = scan.finish(nodes).0;
+        tree.schedule_retry(0);
+        tree
+    }
+
+    /// Parses a file listed by `load_lazy`, replacing its pending node with the location,
+    /// or dropping the node and returning false if the file does not load
+    pub fn load_pending(&mut self, path: &Path) -> bool {
+        fn find<'a>(nodes: &'a mut Vec<Node>, path: &Path) -> Option<(&'a mut Vec<Node>, usize)> {
+            let index = nodes.iter().position(|node| match node {
+                Node::Pending { path: pending, .. } => pending == path,
+                _ => false,
+            });
+            if let Some(index) = index {
+                return Some((nodes, index));
+            }
+            nodes.iter_mut().find_map(|node| match node {
+                Node::Directory { children, .. } => find(children, path),
+                _ => None,
+            })
+        }
+
+        let Some((nodes, index)) = find(&mut self.nodes, path) else {
+            return false;
+        };
+        let mut scan = Scan::default();
+        let loaded = match scan.load(path.to_path_buf()) {
+            Some(location) => {
+                nodes[index] = Node::Location(location);
+                true
+            }
+            None => {
+                nodes.remove(index);
+                false
+            }
+        };
+        let new_errors = scan.errors.len();
+        self.errors.splice(self.checked..self.checked, scan.errors);
+        self.checked += new_errors;
+        self.records.extend(scan.records);
+        self.count(self.summary.files);
+        if self.retry.is_none() {
+            self.schedule_retry(0);
+        }
+        loaded
+    }
+
+    /// Drops the errors of the position checks, to run them again
+    pub fn clear_checks(&mut self) {
+        self.errors.truncate(self.checked);
+    }
+
+    /// Fills in the summary for `files` scanned files
+    fn count(&mut self, files: usize) {
+        fn pending(nodes: &[Node]) -> usize {
+            nodes
+                .iter()
+                .map(|node| match node {
+                    Node::Directory { children, .. } => pending(children),
+                    Node::Location(_) => 0,
+                    Node::Pending { .. } => 1,
+                })
+                .sum()
+        }
+
+        let locations = self.locations();
+        let objectives = || locations.iter().flat_map(|location| &location.data.objectives);
+        let summary = LoadSummary {
+            files,
+            parsed: locations.len(),
+            pending: pending(&self.nodes),
+            objectives: objectives().count(),
+            missing_pos: objectives().filter(|objective| objective.pos.is_none()).count(),
+        };
+        self.summary = summary;
+    }
+
+    /// Rescans `dir`, re-reading only files that are new or changed, or whose includes
+    /// changed, and dropping deleted ones; in a lazy tree, files not opened yet stay pending
+    pub fn refresh(&mut self, dir: &Path) -> RefreshSummary {
+        let mut scan = Scan {
+            previous_records: std::mem::take(&mut self.records),
+            lazy: self.lazy,
+            ..Scan::default()
+        };
+        take_locations(std::mem::take(&mut self.nodes), &mut scan.previous_locations);
+        let nodes = load_directory(dir, &mut scan);
+        let (tree, refresh) = scan.finish(nodes);
+        let retries = self.retry.map_or(0, |(_, retries)| retries);
+        *self = tree;
+        self.schedule_retry(retries);
+        refresh
+    }
+
+    /// Returns when to call `refresh` to re-read files that were truncated when read, as
+    /// while an editor was saving them; `None` without such files or after the last retry
+    pub fn retry_at(&self) -> Option<Instant> {
+        self.retry.map(|(at, _)| at)
+    }
+
+    /// Returns true once the refresh asked for by `retry_at` is due
+    pub fn retry_due(&self) -> bool {
+        self.retry_at().is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Asks for another refresh if any file was truncated and fewer than every retry in
+    /// `RETRY_DELAYS` was made
+    fn schedule_retry(&mut self, retries: usize) {
+        let truncated = self.errors.iter().any(|error| error.truncated);
+        self.retry = RETRY_DELAYS
+            .get(retries)
+            .filter(|_| truncated)
+            .map(|delay| (Instant::now() + *delay, retries + 1));
+    }
+
+    /// Returns every loaded location in tree order
+    pub fn locations(&self) -> Vec<&Location> {
+        fn collect<'a>(nodes: &'a [Node], locations: &mut Vec<&'a Location>) {
+            for node in nodes {
+                match node {
+                    Node::Directory { children, .. } => collect(children, locations),
+                    Node::Location(location) => locations.push(location),
+                    Node::Pending { .. } => {}
+                }
+            }
+        }
+
+        let mut locations = Vec::new();
+        collect(&self.nodes, &mut locations);
+        locations
+    }
+
+    /// Returns the first objective named `name` in tree order, with its location
+    pub fn find_objective(&self, name: &str) -> Option<(&Location, &Position)> {
+        self.locations().into_iter().find_map(|location| {
+            let objective = location.data.objectives.iter().find(|o| o.name == name)?;
+            Some((location, objective))
+        })
+    }
+
+    /// Reports objectives whose position lies outside the bounds configured for their map
+    pub fn check_map_bounds(&mut self, bounds: &[MapBounds]) {
+        let mut errors: Vec<LoadError> = Vec::new();
+        for location in self.locations() {
+            for objective in &location.data.objectives {
+                let (Some(map), Some(pos)) = (objective.map, objective.pos) else {
+                    continue;
+                };
+                let Some(bounds) = bounds.iter().find(|bounds| bounds.map == map) else {
+                    continue;
+                };
+                if bounds.contains(pos) {
+                    continue;
+                }
+                // Included objectives show up in every including location, report them once
+                let (path, line) = match &objective.source {
+                    Some(source) => (&source.path, Some(source.line)),
+                    None => (&location.path, None),
+                };
+                if errors.iter().any(|error| error.path == *path && error.line == line) {
+                    continue;
+                }
+                errors.push(LoadError {
+                    path: path.clone(),
+                    message: format!(
+                        "\"{}\" at {} is outside map {}, expected {} to {}",
+                        objective.name,
+                        format_pos(pos),
+                        map,
+                        format_pos(bounds.min),
+                        format_pos(bounds.max)
+                    ),
+                    pos_issues: Vec::new(),
+                    line,
+                    truncated: false,
+                });
+            }
+        }
+        self.errors.extend(errors);
+    }
+
+    /// Compares this tree as the base with `other`; an objective listed in several locations
+    /// counts once, as first listed
+    pub fn diff(&self, other: &DataTree) -> DataDiff {
+        fn by_key(tree: &DataTree) -> BTreeMap<ObjectiveKey, &Position> {
+            let mut objectives = BTreeMap::new();
+            for location in tree.locations() {
+                for objective in &location.data.objectives {
+                    objectives.entry(objective.key()).or_insert(objective);
+                }
+            }
+            objectives
+        }
+
+        let base = by_key(self);
+        let other = by_key(other);
+        let mut diff = DataDiff::default();
+        for (key, &objective) in &base {
+            match other.get(key) {
+                None => diff.removed.push(objective.clone()),
+                Some(&other) if other.pos == objective.pos && other.hint == objective.hint => {}
+                Some(&other) => diff.changed.push((objective.clone(), other.clone())),
+            }
+        }
+        let added = other.iter().filter(|(key, _)| !base.contains_key(key));
+        diff.added = added.map(|(_, &objective)| objective.clone()).collect();
+        diff
+    }
+
+    /// Reports objectives whose map differs from the one `pattern` reads from their file's
+    /// path below `data_dir`, such as a map 15 objective in `maps/14.json`
+    pub fn check_path_maps(&mut self, pattern: &Regex, data_dir: &Path) {
+        let mut errors: Vec<LoadError> = Vec::new();
+        for location in self.locations() {
+            for objective in &location.data.objectives {
+                let Some(map) = objective.map else {
+                    continue;
+                };
+                // Included objectives are checked against the file they are written in
+                let (path, line) = match &objective.source {
+                    Some(source) => (&source.path, Some(source.line)),
+                    None => (&location.path, None),
+                };
+                let relative = path.strip_prefix(data_dir).unwrap_or(path);
+                let relative: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+                let expected = pattern
+                    .captures(&relative.join("/"))
+                    .and_then(|captures| captures.get(1)?.as_str().parse::<i32>().ok());
+                let Some(expected) = expected.filter(|expected| *expected != map) else {
+                    continue;
+                };
+                if errors.iter().any(|error| error.path == *path && error.line == line) {
+                    continue;
+                }
+                errors.push(LoadError {
+                    path: path.clone(),
+                    message: format!(
+                        "\"{}\" is on map {}, but its file is for map {}",
+                        objective.name, map, expected
+                    ),
+                    pos_issues: Vec::new(),
+                    line,
+                    truncated: false,
+                });
+            }
+        }
+        self.errors.extend(errors);
+    }
+
+    /// Reports objectives with identical positions, usually a copy-paste mistake, once per
+    /// shared position; only within each location unless `across_files` is set
+    pub fn check_duplicate_positions(&mut self, across_files: bool) {
+        struct Group<'a> {
+            scope: Option<&'a Path>,
+            pos: [f32; 3],
+            objectives: Vec<(&'a str, &'a Path, Option<usize>)>,
+        }
+
+        let mut groups: Vec<Group> = Vec::new();
+        let mut index: HashMap<(Option<&Path>, [u32; 3]), usize> = HashMap::new();
+        let mut seen = HashSet::new();
+        for location in self.locations() {
+            for objective in &location.data.objectives {
+                let Some(pos) = objective.pos else {
+                    continue;
+                };
+                let (path, line) = match &objective.source {
+                    Some(source) => (source.path.as_path(), Some(source.line)),
+                    None => (location.path.as_path(), None),
+                };
+                // Included objectives show up in every including location, count them once
+                if line.is_some() && !seen.insert((path, line)) {
+                    continue;
+                }
+                let scope = (!across_files).then_some(location.path.as_path());
+                let group = *index.entry((scope, pos.map(f32::to_bits))).or_insert_with(|| {
+                    groups.push(Group {
+                        scope,
+                        pos,
+                        objectives: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+                groups[group].objectives.push((&objective.name, path, line));
+            }
+        }
+
+        let mut errors = Vec::new();
+        for group in groups.iter().filter(|group| group.objectives.len() > 1) {
+            let names: Vec<String> = group
+                .objectives
+                .iter()
+                .map(|&(name, path, line)| {
+                    let file = path.file_name().unwrap_or_default().to_string_lossy();
+                    match (group.scope, line) {
+                        (Some(_), Some(line)) => format!("\"{}\" (line {})", name, line),
+                        (None, Some(line)) => format!("\"{}\" ({}:{})", name, file, line),
+                        (_, None) => format!("\"{}\"", name),
+                    }
+                })
+                .collect();
+            let (_, path, line) = group.objectives[0];
+            errors.push(LoadError {
+                path: path.to_path_buf(),
+                message: format!(
+                    "{} objectives share the position {}: {}",
+                    names.len(),
+                    format_pos(group.pos),
+                    names.join(", ")
+                ),
+                pos_issues: Vec::new(),
+                line,
+                truncated: false,
+            });
+        }
+        self.errors.extend(errors);
+    }
+}
+
+/// Returns true if `pos` is an array of exactly three finite numbers
+fn is_valid_pos(pos: &serde_json::Value) -> bool {
+    pos.as_array().is_some_and(|axes| {
+        axes.len() == 3
+            && axes
+                .iter()
+                .all(|axis| axis.as_f64().is_some_and(f64::is_finite))
+    })
+}
+
+/// Finds objectives with an invalid `pos` in a file that failed to parse
+fn find_pos_issues(json_content: &str) -> Vec<PosIssue> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json_content) else {
+        return Vec::new();
+    };
+    let objectives = value.as_array().or_else(|| value.get("objectives")?.as_array());
+    let Some(objectives) = objectives else {
+        return Vec::new();
+    };
+
+    let lines = objective_lines(json_content);
+    objectives
+        .iter()
+        .enumerate()
+        .filter_map(|(index, objective)| {
+            let pos = objective.get("pos")?;
+            if pos.is_null() || is_valid_pos(pos) {
+                return None;
+            }
+            Some(PosIssue {
+                index,
+                name: objective
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or(UNNAMED)
+                    .to_string(),
+                pos: pos.clone(),
+                line: lines.get(index).copied(),
+            })
+        })
+        .collect()
+}
+
+/// Removes a leading UTF-8 byte order mark, which some Windows editors write and
+/// `serde_json` rejects; returns the rest and whether a mark was removed
+pub fn strip_bom(content: &str) -> (&str, bool) {
+    match content.strip_prefix('\u{feff}') {
+        Some(rest) => (rest, true),
+        None => (content, false),
+    }
+}
+
+/// Replaces blank objective names with a placeholder and returns their indices
+fn replace_empty_names(data: &mut LocationData) -> Vec<usize> {
+    let mut indices = Vec::new();
+    for (index, objective) in data.objectives.iter_mut().enumerate() {
+        if objective.name.trim().is_empty() {
+            objective.name = UNNAMED.to_string();
+            indices.push(index);
+        }
+    }
+    indices
+}
+
+/// Makes icon paths relative to the data file's directory usable from anywhere
+fn resolve_icons(data: &mut LocationData, path: &Path) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for icon in data.objectives.iter_mut().filter_map(|o| o.icon.as_mut()) {
+        *icon = dir.join(&*icon);
+    }
+}
+
+/// Recursively loads directory contents into tree nodes; directories that cannot be listed
+/// are reported unless they do not exist
+fn load_directory(path: &Path, scan: &mut Scan) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                scan.errors.push(LoadError::new(path, format!("could not read directory: {}", e)));
+            }
+            return nodes;
+        }
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(e) => scan.errors.push(LoadError::new(
+                path,
+                format!("could not read a directory entry: {}", e),
+            )),
+        }
+    }
+    paths.sort_by_cached_key(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        (!path.is_dir(), name.to_lowercase(), name.into_owned())
+    });
+
+    for path in paths {
+        let name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+
+        if path.is_dir() {
+            let meta_path = path.join(DIRECTORY_META_FILE);
+            let meta = if meta_path.is_file() {
+                scan.load_meta(meta_path)
+            } else {
+                DirectoryMeta::default()
+            };
+            nodes.push(Node::Directory {
+                name: meta.name.clone().unwrap_or_else(|| name.to_string()),
+                meta,
+                children: load_directory(&path, scan),
+                path,
+            });
+        } else if is_data_file(&path) && name != DIRECTORY_META_FILE {
+            // A lazy refresh keeps loaded files up to date but leaves new ones for later
+            if scan.lazy && !scan.previous_locations.contains_key(&path) {
+                scan.files += 1;
+                nodes.push(Node::Pending {
+                    name: location_name(&path),
+                    path,
+                });
+            } else if let Some(location) = scan.load(path) {
+                nodes.push(Node::Location(location));
+            }
+        }
+    }
+    // Stable, so directories of equal priority stay in name order
+    nodes.sort_by_key(|node| match node {
+        Node::Directory { meta, .. } => (false, -i64::from(meta.priority)),
+        Node::Location(_) | Node::Pending { .. } => (true, 0),
+    });
+    nodes
+}
+
+/// Reads a directory's `_meta.json`, falling back to the defaults if it is invalid
+fn read_directory_meta(path: &Path, errors: &mut Vec<LoadError>) -> DirectoryMeta {
+    let Ok(content) = read_reported(path, errors) else {
+        return DirectoryMeta::default();
+    };
+    let meta = match serde_json::from_str::<DirectoryMeta>(strip_bom(&content).0) {
+        Ok(meta) => meta,
+        Err(e) => {
+            eprintln!("Error parsing {}: {}", path.display(), e);
+            errors.push(LoadError::new(path, e.to_string()));
+            return DirectoryMeta::default();
+        }
+    };
+    if let Some(color) = meta.color.as_deref().filter(|color| parse_hex_color(color).is_none()) {
+        errors.push(LoadError::new(path, format!("invalid color {:?}, expected #RRGGBB", color)));
+    }
+    meta
+}
+
+/// Counts the data files below `dir` without reading them
+pub fn count_data_files(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                count_data_files(&path)
+            } else {
+                usize::from(is_data_file(&path))
+            }
+        })
+        .sum()
+}
+
+/// Returns true if `path` is a gzip-compressed data file
+pub fn is_gzip(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".json.gz"))
+}
+
+/// Returns true if `path` is a data file, plain or gzip-compressed
+fn is_data_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json") || is_gzip(path)
+}
+
+/// Start of the error `read_data_file` returns for `.json.gz` files that are not valid gzip
+const DECOMPRESS_ERROR: &str = "could not decompress";
+
+/// Reads a data file as text, decompressing `.json.gz` files
+pub fn read_data_file(path: &Path) -> Result<String, String> {
+    if !is_gzip(path) {
+        return fs::read_to_string(path).map_err(|e| e.to_string());
+    }
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut content = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("{}: {}", DECOMPRESS_ERROR, e))?;
+    Ok(content)
+}
+
+/// Writes a data file, compressing it again if it is a `.json.gz` file
+pub fn write_data_file(path: &Path, content: &str) -> Result<(), String> {
+    if !is_gzip(path) {
+        return fs::write(path, content).map_err(|e| e.to_string());
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(content.as_bytes())
+        .and_then(|()| encoder.finish())
+        .map_err(|e| e.to_string())?;
+    fs::write(path, compressed).map_err(|e| e.to_string())
+}
+
+/// Reads a data file, reporting failures such as denied permissions in the errors panel
+fn read_reported(path: &Path, errors: &mut Vec<LoadError>) -> Result<String, String> {
+    read_data_file(path).inspect_err(|e| {
+        // Decompression errors already say what is wrong with the file
+        let message = if e.starts_with(DECOMPRESS_ERROR) {
+            e.clone()
+        } else {
+            format!("could not read file: {}", e)
+        };
+        errors.push(LoadError::new(path, message));
+    })
+}
+
+/// Name of a location loaded from a bare array: the file name without `.json` or `.json.gz`
+fn location_name(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let name = name.strip_suffix(".gz").unwrap_or(&name);
+    name.strip_suffix(".json").unwrap_or(name).to_string()
+}
+
+/// Reads a data file, ignoring a byte order mark; files whose JSON ends early are marked
+/// `truncated`, to be re-read by the refresh `DataTree::retry_at` asks for
+fn read_location_data(path: &Path, errors: &mut Vec<LoadError>) -> Result<LocationData, String> {
+    let content = read_reported(path, errors)?;
+    let (json_content, had_bom) = strip_bom(&content);
+    if had_bom {
+        errors.push(LoadError::new(
+            path,
+            "ignored the UTF-8 byte order mark at the start of the file",
+        ));
+    }
+    // Files may also be a bare array of objectives, named after the file
+    let bare = json_content.trim_start().starts_with('[');
+    #[cfg(debug_assertions)]
+    if bare {
+        eprintln!("Loading {} as a bare objectives array", path.display());
+    }
+    let parsed = if bare {
+        serde_json::from_str(json_content).map(|objectives| LocationData {
+            name: location_name(path),
+            objectives,
+            color: None,
+            include: Vec::new(),
+        })
+    } else {
+        serde_json::from_str(json_content)
+    };
+    let mut data: LocationData = parsed.map_err(|e| {
+        errors.push(LoadError {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+            pos_issues: find_pos_issues(json_content),
+            line: Some(e.line()),
+            truncated: e.is_eof(),
+        });
+        e.to_string()
+    })?;
+    if data.objectives.is_empty() && !bare {
+        let value: serde_json::Value = serde_json::from_str(json_content).unwrap_or_default();
+        if !value.get("objectives").is_some_and(serde_json::Value::is_array) {
+            errors.push(LoadError::new(
+                path,
+                "\"objectives\" is missing or null, loaded as an empty location",
+            ));
+        }
+    }
+    let lines = objective_lines(json_content);
+    for (index, (objective, line)) in data.objectives.iter_mut().zip(lines).enumerate() {
+        objective.source = Some(SourceLine {
+            path: path.to_path_buf(),
+            line,
+            index,
+        });
+    }
+    Ok(data)
+}
+
+/// Returns the 1-based line each element of the top-level `objectives` array, or of a bare
+/// top-level array, starts on.
+/// serde_json does not report where values were read from, so this scans the text,
+/// skipping over strings and tracking nesting.
+fn objective_lines(json_content: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut line = 1;
+    let mut depth = 0usize;
+    let mut string_start = None;
+    let mut escaped = false;
+    // Last string read directly inside the top-level object, the key of what follows
+    let mut last_key = "";
+    let mut in_objectives = false;
+    let mut expect_element = false;
+    // Nesting depth of the elements, 1 when the file is a bare array
+    let mut element_depth = 2;
+    for (i, byte) in json_content.bytes().enumerate() {
+        if byte == b'\n' {
+            line += 1;
+        }
+        if let Some(start) = string_start {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                string_start = None;
+                if depth == 1 {
+                    last_key = &json_content[start..i];
+                }
+            }
+            continue;
+        }
+        let in_elements = in_objectives && depth == element_depth;
+        if in_elements && expect_element && !byte.is_ascii_whitespace() {
+            expect_element = false;
+            if byte != b']' {
+                lines.push(line);
+            }
+        }
+        match byte {
+            b'"' => string_start = Some(i + 1),
+            b'{' | b'[' => {
+                depth += 1;
+                let bare = depth == 1 && byte == b'[';
+                let wrapped =
+                    depth == 2 && byte == b'[' && element_depth == 2 && last_key == "objectives";
+                if bare {
+                    element_depth = 1;
+                }
+                if bare || wrapped {
+                    in_objectives = true;
+                    expect_element = true;
+                }
+            }
+            b'}' | b']' => {
+                if depth == element_depth {
+                    in_objectives = false;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            b',' if in_objectives && depth == element_depth => expect_element = true,
+            _ => {}
+        }
+    }
+    lines
+}
+
+/// Appends the objectives of every file in `data.include`, following nested includes;
+/// `stack` holds the files currently being included, to detect cycles, and every file
+/// read is added to `included`
+fn resolve_includes(
+    data: &mut LocationData,
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    errors: &mut Vec<LoadError>,
+    included: &mut Vec<PathBuf>,
+) {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for include in std::mem::take(&mut data.include) {
+        let include_path = dir.join(&include);
+        let canonical = fs::canonicalize(&include_path).unwrap_or_else(|_| include_path.clone());
+        if stack.contains(&canonical) {
+            errors.push(LoadError::new(
+                path,
+                format!("include cycle through {}, skipped", include.display()),
+            ));
+            continue;
+        }
+        included.push(include_path.clone());
+        match read_location_data(&include_path, errors) {
+            Ok(mut nested) => {
+                resolve_icons(&mut nested, &include_path);
+                replace_empty_names(&mut nested);
+                stack.push(canonical);
+                resolve_includes(&mut nested, &include_path, stack, errors, included);
+                stack.pop();
+                data.objectives.append(&mut nested.objectives);
+            }
+            Err(e) => errors.push(LoadError::new(
+                path,
+                format!("could not include {}: {}", include.display(), e),
+            )),
+        }
+    }
+}
+
+/// Loads one location file with its includes, recording problems in `errors` and the
+/// files it includes in `included`
+fn load_location(
+    path: PathBuf,
+    errors: &mut Vec<LoadError>,
+    included: &mut Vec<PathBuf>,
+) -> Option<Location> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut data = match read_location_data(&path, errors) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Error parsing {}: {}", name, e);
+            return None;
+        }
+    };
+
+    resolve_icons(&mut data, &path);
+    let unnamed = replace_empty_names(&mut data);
+    if !unnamed.is_empty() {
+        let indices: Vec<_> = unnamed
+            .iter()
+            .map(|index| format!("objectives[{}]", index))
+            .collect();
+        let message = format!("empty name in {}, shown as \"{}\"", indices.join(", "), UNNAMED);
+        eprintln!("Error validating {}: {}", name, message);
+        errors.push(LoadError::new(&path, message));
+    }
+    let canonical = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+    resolve_includes(&mut data, &path, &mut vec![canonical], errors, included);
+    if let Some(color) = &data.color {
+        if parse_hex_color(color).is_none() {
+            errors.push(LoadError::new(
+                &path,
+                format!("invalid color {:?}, expected #RRGGBB", color),
+            ));
+        }
+    }
+    let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+    Some(Location {
+        path,
+        modified,
+        data,
+    })
+}
+
+/// Writes the sample data file into `dir` without overwriting
+pub fn create_example_file(dir: &Path) -> Result<PathBuf, String> {
+    let path = dir.join(EXAMPLE_FILE_NAME);
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    fs::create_dir_all(dir)
+        .and_then(|_| fs::write(&path, EXAMPLE_FILE))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::ops::Deref;
+
+    /// A directory below the system's temporary directory, deleted again when dropped so
+    /// failing tests don't leave it behind; shared with the tests of the other modules
+    pub(crate) struct TempDir(PathBuf);
+
+    impl TempDir {
+        /// Creates an empty directory whose name starts with `tpgui-name`
+        pub(crate) fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("tpgui-{}-{}", name, std::process::id()));
+            // Left over from a run of the same process ID that did not finish
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn parse_position(json: &str) -> Result<Position, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn position_with_every_field() {
+        let position = parse_position(
+            r#"{
+                "name": "Lion's Arch",
+                "hint": "Waypoint",
+                "map": 50,
+                "pos": [1.5, -2.0, 3.25],
+                "hidden": true,
+                "priority": 3,
+                "chat_code": "[&BH4BAAA=]",
+                "icon": "waypoint.png"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(position.name, "Lion's Arch");
+        assert_eq!(position.hint.as_deref(), Some("Waypoint"));
+        assert_eq!(position.map, Some(50));
+        assert_eq!(position.pos, Some([1.5, -2.0, 3.25]));
+        assert!(position.hidden);
+        assert_eq!(position.priority, Some(3));
+        assert_eq!(position.chat_code.as_deref(), Some("[&BH4BAAA=]"));
+        assert_eq!(position.icon, Some(PathBuf::from("waypoint.png")));
+    }
+
+    #[test]
+    fn orders_by_priority_then_name() {
+        let position = |name: &str, priority: Option<i32>| {
+            let json = serde_json::json!({ "name": name, "priority": priority });
+            serde_json::from_value::<Position>(json).unwrap()
+        };
+        let mut positions = [
+            position("Unset", None),
+            position("Low", Some(-1)),
+            position("B", Some(5)),
+            position("Also unset", None),
+            position("A", Some(5)),
+            position("Zero", Some(0)),
+        ];
+        positions.sort_by(Position::cmp_priority);
+        let names: Vec<_> = positions.iter().map(|position| position.name.as_str()).collect();
+        assert_eq!(names, ["A", "B", "Zero", "Low", "Also unset", "Unset"]);
+    }
+
+    #[test]
+    fn position_json_leaves_out_unset_fields() {
+        let json = r#"{ "name": "Round trip", "map": 15, "pos": [1.0, 2.0, 3.0] }"#;
+        let copied = parse_position(json).unwrap().to_json();
+        let value: serde_json::Value = serde_json::from_str(&copied).unwrap();
+        assert_eq!(value, serde_json::from_str::<serde_json::Value>(json).unwrap());
+    }
+
+    #[test]
+    fn position_optional_fields_default() {
+        let position = parse_position(r#"{ "name": "Bare" }"#).unwrap();
+        assert_eq!(position.hint, None);
+        assert!(position.aliases.is_empty());
+        assert_eq!(position.map, None);
+        assert_eq!(position.pos, None);
+        assert!(!position.hidden);
+        assert_eq!(position.chat_code, None);
+        assert_eq!(position.icon, None);
+    }
+
+    #[test]
+    fn position_accepts_null_optionals() {
+        let position = parse_position(r#"{ "name": "Nulls", "hint": null, "pos": null }"#).unwrap();
+        assert_eq!(position.hint, None);
+        assert_eq!(position.pos, None);
+    }
+
+    #[test]
+    fn position_requires_name() {
+        assert!(parse_position(r#"{ "pos": [1, 2, 3] }"#).is_err());
+    }
+
+    #[test]
+    fn position_rejects_wrong_pos_length() {
+        assert!(parse_position(r#"{ "name": "Short", "pos": [1, 2] }"#).is_err());
+        assert!(parse_position(r#"{ "name": "Long", "pos": [1, 2, 3, 4] }"#).is_err());
+    }
+
+    #[test]
+    fn position_rejects_wrong_types() {
+        assert!(parse_position(r#"{ "name": "Map", "map": "fifteen" }"#).is_err());
+        assert!(parse_position(r#"{ "name": "Pos", "pos": ["1", "2", "3"] }"#).is_err());
+        assert!(parse_position(r#"{ "name": 5 }"#).is_err());
+    }
+
+    #[test]
+    fn location_data_defaults() {
+        let data: LocationData =
+            serde_json::from_str(r#"{ "name": "Empty", "objectives": [] }"#).unwrap();
+        assert_eq!(data.name, "Empty");
+        assert!(data.objectives.is_empty());
+        assert_eq!(data.color, None);
+        assert!(data.include.is_empty());
+    }
+
+    #[test]
+    fn location_data_without_objectives_is_empty() {
+        for json in [r#"{ "name": "None" }"#, r#"{ "name": "Null", "objectives": null }"#] {
+            let data: LocationData = serde_json::from_str(json).unwrap();
+            assert!(data.objectives.is_empty());
+        }
+        let not_a_list = r#"{ "name": "Object", "objectives": {} }"#;
+        assert!(serde_json::from_str::<LocationData>(not_a_list).is_err());
+    }
+
+    #[test]
+    fn location_data_reads_include_alias() {
+        let data: LocationData = serde_json::from_str(
+            r#"{ "name": "Alias", "objectives": [], "$include": ["shared.json"] }"#,
+        )
+        .unwrap();
+        assert_eq!(data.include, vec![PathBuf::from("shared.json")]);
+    }
+
+    #[test]
+    fn location_data_rejects_malformed_json() {
+        let truncated = r#"{ "name": "Cut", "objectives": [ { "name": "A" "#;
+        let error = serde_json::from_str::<LocationData>(truncated).err().unwrap();
+        assert!(error.is_eof());
+        let trailing_comma = r#"{ "name": "Comma", "objectives": [], }"#;
+        assert!(serde_json::from_str::<LocationData>(trailing_comma).is_err());
+    }
+
+    #[test]
+    fn example_file_parses() {
+        let data: LocationData = serde_json::from_str(EXAMPLE_FILE).unwrap();
+        assert!(!data.objectives.is_empty());
+        assert!(data.color().is_some());
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(parse_hex_color("#FF0000"), Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(parse_hex_color("FF0000"), None);
+        assert_eq!(parse_hex_color("#FF00"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+    }
+
+    #[test]
+    fn summary_counts_files_and_objectives() {
+        let dir = TempDir::new("summary");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        let objectives = r#"[{ "name": "A", "pos": [1, 2, 3] }, { "name": "B" }]"#;
+        let valid = format!(r#"{{ "name": "Valid", "objectives": {} }}"#, objectives);
+        fs::write(dir.join("valid.json"), valid).unwrap();
+        fs::write(dir.join("nested").join("broken.json"), "{ not json }").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let summary = DataTree::load(&dir).summary;
+        let expected = LoadSummary {
+            files: 2,
+            parsed: 1,
+            pending: 0,
+            objectives: 2,
+            missing_pos: 1,
+        };
+        assert_eq!(summary, expected);
+        assert_eq!(
+            summary.to_string(),
+            "2 files scanned, 1 parsed OK, 1 failed, 2 objectives, 1 missing positions"
+        );
+    }
+
+    #[test]
+    fn flags_positions_outside_map_bounds() {
+        let dir = TempDir::new("bounds");
+        let objectives = r#"[
+            { "name": "Inside", "map": 15, "pos": [10, 20, 30] },
+            { "name": "Outside", "map": 15, "pos": [10, 900, 30] },
+            { "name": "Other map", "map": 18, "pos": [10, 900, 30] }
+        ]"#;
+        let content = format!(r#"{{ "name": "Bounds", "objectives": {} }}"#, objectives);
+        fs::write(dir.join("bounds.json"), content).unwrap();
+
+        let mut tree = DataTree::load(&dir);
+        tree.check_map_bounds(&[MapBounds {
+            map: 15,
+            min: [0.0, 0.0, 0.0],
+            max: [100.0, 100.0, 100.0],
+        }]);
+        assert_eq!(tree.errors.len(), 1);
+        assert_eq!(tree.errors[0].line, Some(3));
+        assert_eq!(
+            tree.errors[0].message,
+            "\"Outside\" at [10, 900, 30] is outside map 15, expected [0, 0, 0] to [100, 100, 100]"
+        );
+    }
+
+    #[test]
+    fn flags_identical_positions() {
+        let dir = TempDir::new("duplicates");
+        let first = r#"{ "name": "First", "objectives": [
+            { "name": "A", "pos": [1, 2, 3] },
+            { "name": "B", "pos": [1, 2, 3] },
+            { "name": "C", "pos": [4, 5, 6] }
+        ] }"#;
+        let second = r#"{ "name": "Second", "objectives": [{ "name": "D", "pos": [4, 5, 6] }] }"#;
+        fs::write(dir.join("first.json"), first).unwrap();
+        fs::write(dir.join("second.json"), second).unwrap();
+
+        let mut within = DataTree::load(&dir);
+        let mut across = DataTree::load(&dir);
+        within.check_duplicate_positions(false);
+        across.check_duplicate_positions(true);
+
+        assert_eq!(within.errors.len(), 1);
+        assert_eq!(
+            within.errors[0].message,
+            "2 objectives share the position [1, 2, 3]: \"A\" (line 2), \"B\" (line 3)"
+        );
+        assert_eq!(across.errors.len(), 2);
+        assert_eq!(
+            across.errors[1].message,
+            "2 objectives share the position [4, 5, 6]: \"C\" (first.json:4), \"D\" (second.json:1)"
+        );
+    }
+
+    #[test]
+    fn flags_objectives_on_another_map_than_their_file() {
+        let dir = TempDir::new("path-maps");
+        fs::create_dir_all(dir.join("maps")).unwrap();
+        let objectives = r#"{ "name": "Fifteen", "objectives": [
+            { "name": "Right", "map": 15 },
+            { "name": "Wrong", "map": 14 },
+            { "name": "Unknown" }
+        ] }"#;
+        fs::write(dir.join("maps").join("15.json"), objectives).unwrap();
+        let other = r#"{ "name": "Other", "objectives": [{ "name": "X", "map": 1 }] }"#;
+        fs::write(dir.join("other.json"), other).unwrap();
+
+        let mut tree = DataTree::load(&dir);
+        tree.check_path_maps(&Regex::new(r"^maps/(\d+)\.json$").unwrap(), &dir);
+
+        assert_eq!(tree.errors.len(), 1);
+        assert_eq!(tree.errors[0].message, "\"Wrong\" is on map 14, but its file is for map 15");
+        assert_eq!(tree.errors[0].line, Some(3));
+    }
+
+    #[test]
+    fn refresh_rereads_only_changed_files() {
+        let dir = TempDir::new("refresh");
+        let location = |name: &str| format!(r#"{{ "name": "{}", "objectives": [] }}"#, name);
+        fs::write(dir.join("kept.json"), location("Kept")).unwrap();
+        fs::write(dir.join("edited.json"), location("Edited")).unwrap();
+        fs::write(dir.join("deleted.json"), location("Deleted")).unwrap();
+        fs::write(dir.join("broken.json"), "{ not json }").unwrap();
+
+        let mut tree = DataTree::load(&dir);
+        assert_eq!(tree.refresh(&dir), RefreshSummary::default());
+        fs::write(dir.join("edited.json"), location("Edited again")).unwrap();
+        fs::remove_file(dir.join("deleted.json")).unwrap();
+        fs::write(dir.join("added.json"), location("Added")).unwrap();
+        let refresh = tree.refresh(&dir);
+
+        let expected = RefreshSummary {
+            changed: 1,
+            added: 1,
+            removed: 1,
+        };
+        assert_eq!(refresh, expected);
+        let names: Vec<_> = tree.locations().iter().map(|l| l.data.name.clone()).collect();
+        assert_eq!(names, ["Added", "Edited again", "Kept"]);
+        assert_eq!(tree.errors.len(), 1);
+    }
+
+    #[test]
+    fn schedules_rereads_of_truncated_files() {
+        let dir = TempDir::new("truncated");
+        let path = dir.join("saving.json");
+        fs::write(&path, r#"{ "name": "Saving", "objectives": [ { "name": "A" "#).unwrap();
+
+        let mut tree = DataTree::load(&dir);
+        assert!(tree.errors[0].truncated);
+        assert!(tree.retry_at().is_some());
+        fs::write(&path, r#"{ "name": "Saving", "objectives": [ { "name": "A" } ] }"#).unwrap();
+        tree.refresh(&dir);
+
+        assert_eq!(tree.locations()[0].data.objectives[0].name, "A");
+        assert!(tree.errors.is_empty());
+        assert!(tree.retry_at().is_none());
+    }
+
+    #[test]
+    fn loads_gzip_data_files() {
+        let dir = TempDir::new("gzip");
+        let path = dir.join("packed.json.gz");
+        write_data_file(&path, r#"{ "name": "Packed", "objectives": [{ "name": "A" }] }"#).unwrap();
+        fs::write(dir.join("corrupt.json.gz"), "not gzip").unwrap();
+
+        let tree = DataTree::load(&dir);
+        let locations = tree.locations();
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].data.objectives[0].name, "A");
+        assert!(tree.errors.iter().any(|e| e.message.starts_with("could not decompress")));
+    }
+
+    #[test]
+    fn finds_objective_lines() {
+        let json = r##"{
+  "name": "Lines [with] {brackets}, \"quotes\"",
+  "color": "#FFFFFF",
+  "objectives": [
+    { "name": "A", "pos": [1, 2, 3] },
+
+    {
+      "name": "B ] }",
+      "hint": "\\"
+    }, {"name": "C"}
+  ],
+  "include": [{}]
+}"##;
+        assert_eq!(objective_lines(json), vec![5, 7, 10]);
+        assert!(objective_lines(r#"{ "name": "Empty", "objectives": [ ] }"#).is_empty());
+    }
+
+    #[test]
+    fn loads_bare_objective_arrays() {
+        let dir = TempDir::new("bare");
+        let path = dir.join("Bare list.json");
+        fs::write(&path, "[\n  { \"name\": \"A\" },\n  { \"name\": \"B\" }\n]").unwrap();
+
+        let mut errors = Vec::new();
+        let data = read_location_data(&path, &mut errors);
+        let data = data.unwrap();
+        assert_eq!(data.name, "Bare list");
+        let lines = data.objectives.iter().map(|o| o.source.as_ref().unwrap().line);
+        assert_eq!(lines.collect::<Vec<_>>(), [2, 3]);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn strips_byte_order_mark() {
+        assert_eq!(strip_bom("\u{feff}{}"), ("{}", true));
+        assert_eq!(strip_bom("{}"), ("{}", false));
+    }
+
+    #[test]
+    fn parses_coordinates_from_pasted_text() {
+        assert_eq!(parse_coordinates("[1.5, -2, 3]"), Some([1.5, -2.0, 3.0]));
+        assert_eq!(parse_coordinates("1.5 -2 3\n"), Some([1.5, -2.0, 3.0]));
+        assert_eq!(parse_coordinates("x: 10; y: 20; z: 30"), Some([10.0, 20.0, 30.0]));
+        assert_eq!(parse_coordinates("1, 2"), None);
+        assert_eq!(parse_coordinates("1, 2, 3, 4"), None);
+        assert_eq!(parse_coordinates("1-2, 3, 4"), None);
+    }
+
+    #[test]
+    fn diffs_two_directories() {
+        let base_dir = TempDir::new("diff-base");
+        let other_dir = TempDir::new("diff-other");
+        let base = r#"{ "name": "Town", "objectives": [
+            { "name": "Gate", "map": 1, "pos": [1, 2, 3] },
+            { "name": "Well", "map": 1, "pos": [4, 5, 6], "hint": "Old" },
+            { "name": "Tower", "map": 1 }
+        ] }"#;
+        let other = r#"{ "name": "Town", "objectives": [
+            { "name": "Gate", "map": 1, "pos": [1, 2, 3] },
+            { "name": "Well", "map": 1, "pos": [4, 5, 6], "hint": "New" },
+            { "name": "Tower", "map": 2 }
+        ] }"#;
+        fs::write(base_dir.join("town.json"), base).unwrap();
+        fs::write(other_dir.join("town.json"), other).unwrap();
+
+        let diff = DataTree::load(&base_dir).diff(&DataTree::load(&other_dir));
+
+        let names = |objectives: &[Position]| -> Vec<_> {
+            objectives.iter().map(|objective| (objective.name.clone(), objective.map)).collect()
+        };
+        assert_eq!(names(&diff.added), [("Tower".to_string(), Some(2))]);
+        assert_eq!(names(&diff.removed), [("Tower".to_string(), Some(1))]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0.hint.as_deref(), Some("Old"));
+        assert_eq!(diff.changed[0].1.hint.as_deref(), Some("New"));
+    }
+
+    #[test]
+    fn applies_directory_metadata() {
+        let dir = TempDir::new("meta");
+        for name in ["alpha", "beta", "gamma"] {
+            fs::create_dir_all(dir.join(name)).unwrap();
+        }
+        let meta = r##"{ "name": "Gamma first", "priority": 5, "color": "#ff0000" }"##;
+        fs::write(dir.join("gamma").join(DIRECTORY_META_FILE), meta).unwrap();
+        fs::write(dir.join("beta").join(DIRECTORY_META_FILE), "{ not json }").unwrap();
+
+        let tree = DataTree::load(&dir);
+
+        let names: Vec<_> = tree
+            .nodes
+            .iter()
+            .map(|node| match node {
+                Node::Directory { name, .. } | Node::Pending { name, .. } => name.as_str(),
+                Node::Location(location) => location.data.name.as_str(),
+            })
+            .collect();
+        assert_eq!(names, ["Gamma first", "alpha", "beta"]);
+        let Node::Directory { meta, .. } = &tree.nodes[0] else {
+            panic!("expected a directory");
+        };
+        assert_eq!(meta.color(), Some([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(tree.summary.files, 0);
+        assert_eq!(tree.errors.len(), 1);
+    }
+
+    #[test]
+    fn loads_pending_files_on_demand() {
+        let dir = TempDir::new("lazy");
+        let file = dir.join("town.json");
+        let json = r#"{ "name": "Town", "objectives": [{ "name": "Gate", "pos": [1, 2, 3] }] }"#;
+        fs::write(&file, json).unwrap();
+
+        let mut tree = DataTree::load_lazy(&dir);
+        assert_eq!(tree.summary.pending, 1);
+        assert!(matches!(&tree.nodes[0], Node::Pending { name, .. } if name == "town"));
+
+        assert!(tree.load_pending(&file));
+        assert_eq!(tree.summary.pending, 0);
+        assert_eq!(tree.summary.parsed, 1);
+        assert_eq!(tree.summary.objectives, 1);
+        assert!(matches!(&tree.nodes[0], Node::Location(location) if location.data.name == "Town"));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    config::Config,
+    data::{DataDiff, DataTree, Position},
+};
+use imgui::{Condition, TreeNodeFlags, Ui};
+use std::path::{Path, PathBuf};
+
+/// The "Compare directories" window, which lists the objectives added, removed and changed
+/// between a base data directory and another one, such as a fork and its upstream
+#[derive(Default)]
+pub struct DiffWindow {
+    pub open: bool,
+    base: PathBuf,
+    other: Option<PathBuf>,
+    diff: Option<DataDiff>,
+}
+
+impl DiffWindow {
+    /// Shows the window with `data_dir` as the base directory
+    pub fn show(&mut self, data_dir: &Path) {
+        if self.base != data_dir {
+            self.base = data_dir.to_path_buf();
+            self.compare();
+        }
+        self.open = true;
+    }
+
+    /// Loads both directories again and compares them
+    fn compare(&mut self) {
+        self.diff = self
+            .other
+            .as_ref()
+            .map(|other| DataTree::load(&self.base).diff(&DataTree::load(other)));
+    }
+
+    /// Lets the user pick the other directory, or the base one unless `other`, and compares
+    /// again once one is picked
+    fn pick(&mut self, other: bool) {
+        let start = match &self.other {
+            Some(dir) if other => dir,
+            _ => &self.base,
+        };
+        let Some(dir) = rfd::FileDialog::new().set_directory(start).pick_folder() else {
+            return;
+        };
+        if other {
+            self.other = Some(dir);
+        } else {
+            self.base = dir;
+        }
+        self.compare();
+    }
+
+    /// Draws the window; `layout` is when the default position and size apply
+    pub fn draw(&mut self, ui: &Ui, config: &Config, layout: Condition) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        ui.window("Compare directories")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([440.0, 420.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                ui.text(format!("Base: {}", self.base.display()));
+                ui.same_line();
+                if ui.small_button("Change...##base") {
+                    self.pick(false);
+                }
+                match &self.other {
+                    Some(other) => ui.text(format!("Other: {}", other.display())),
+                    None => ui.text_disabled("Other: none"),
+                }
+                ui.same_line();
+                if ui.small_button("Change...##other") {
+                    self.pick(true);
+                }
+                ui.disabled(self.other.is_none(), || {
+                    if ui.button("Compare again") {
+                        self.compare();
+                    }
+                });
+                ui.separator();
+
+                let Some(diff) = &self.diff else {
+                    ui.text_disabled("Pick another directory to compare the base with");
+                    return;
+                };
+                ui.text(format!(
+                    "{} added, {} removed, {} changed",
+                    diff.added.len(),
+                    diff.removed.len(),
+                    diff.changed.len()
+                ));
+                let sections = [("Added", &diff.added), ("Removed", &diff.removed)];
+                for (title, objectives) in sections {
+                    let label = format!("{} ({})###{}", title, objectives.len(), title);
+                    if ui.collapsing_header(label, TreeNodeFlags::DEFAULT_OPEN) {
+                        for objective in objectives {
+                            ui.bullet_text(describe(objective, config));
+                        }
+                    }
+                }
+                let label = format!("Changed ({})###Changed", diff.changed.len());
+                if ui.collapsing_header(label, TreeNodeFlags::DEFAULT_OPEN) {
+                    for (base, other) in &diff.changed {
+                        ui.bullet_text(name_and_map(base));
+                        ui.indent();
+                        if base.pos != other.pos {
+                            let pos = |objective: &Position| match objective.pos {
+                                Some(pos) => format!("[{}]", config.format_coords(pos)),
+                                None => "no position".to_string(),
+                            };
+                            ui.text_disabled(format!("Position: {} -> {}", pos(base), pos(other)));
+                        }
+                        if base.hint != other.hint {
+                            let hint = |objective: &Position| match &objective.hint {
+                                Some(hint) => format!("\"{}\"", hint),
+                                None => "none".to_string(),
+                            };
+                            ui.text_disabled(format!("Hint: {} -> {}", hint(base), hint(other)));
+                        }
+                        ui.unindent();
+                    }
+                }
+            });
+        self.open = open;
+    }
+}
+
+/// An objective's name, with its map if it has one
+fn name_and_map(objective: &Position) -> String {
+    match objective.map {
+        Some(map) => format!("{} (map {})", objective.name, map),
+        None => objective.name.clone(),
+    }
+}
+
+/// An objective's name and map with its position, for the added and removed lists
+fn describe(objective: &Position, config: &Config) -> String {
+    match objective.pos {
+        Some(pos) => format!("{} [{}]", name_and_map(objective), config.format_coords(pos)),
+        None => name_and_map(objective),
+    }
+}
@@ -0,0 +1,302 @@
+use crate::data::{self, PosIssue};
+use serde_json::Value;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Ways to repair an invalid `pos`
+#[derive(Clone, Copy)]
+pub enum PosFix {
+    /// Keep the first three axes, replacing non-numbers and missing axes with 0
+    PadOrTruncate,
+    /// Drop the `pos` field
+    Remove,
+}
+
+/// Copies `path` to the first free `<file>.bak`, `<file>.bak2`, ... and returns the copy
+fn backup_file(path: &Path) -> Result<PathBuf, String> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("data");
+    let backup = (1..)
+        .map(|n| {
+            let suffix = if n == 1 { String::new() } else { n.to_string() };
+            path.with_file_name(format!("{}.bak{}", file_name, suffix))
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("backup candidates are unbounded");
+    fs::copy(path, &backup)
+        .map_err(|e| format!("Could not back up {}: {}", path.display(), e))?;
+    Ok(backup)
+}
+
+/// Returns the objectives of a data file, either its `objectives` array or the whole file
+/// when it is a bare array
+fn objectives_mut(value: &mut Value) -> Option<&mut Vec<Value>> {
+    match value {
+        Value::Array(objectives) => Some(objectives),
+        _ => value.get_mut("objectives").and_then(Value::as_array_mut),
+    }
+}
+
+/// Returns objective `index` of the data file `value` read from `path`, failing if it is no
+/// longer named `name` because the file changed since it was loaded
+fn named_objective<'a>(
+    path: &Path,
+    value: &'a mut Value,
+    index: usize,
+    name: &str,
+) -> Result<&'a mut serde_json::Map<String, Value>, String> {
+    objectives_mut(value)
+        .and_then(|o| o.get_mut(index))
+        .and_then(Value::as_object_mut)
+        .filter(|objective| {
+            // Blank names are loaded as a placeholder, so any blank name matches
+            let current = objective.get("name").and_then(Value::as_str).unwrap_or("");
+            current == name || current.trim().is_empty()
+        })
+        .ok_or_else(|| format!("{} changed since it was loaded", path.display()))
+}
+
+/// Backs up `path`, applies `edit` to its JSON and writes it back pretty-printed
+fn rewrite_json(
+    path: &Path,
+    edit: impl FnOnce(&mut Value) -> Result<(), String>,
+) -> Result<PathBuf, String> {
+    let content = data::read_data_file(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let (content, _) = data::strip_bom(&content);
+    let mut value: Value = serde_json::from_str(content)
+        .map_err(|e| format!("Could not parse {}: {}", path.display(), e))?;
+    edit(&mut value)?;
+
+    let backup = backup_file(path)?;
+    let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    data::write_data_file(path, &content)
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    Ok(backup)
+}
+
+/// Repairs the position reported by `issue` in `path`, returning the backup path; fails if
+/// that objective is no longer named as in `issue`
+pub fn fix_pos(path: &Path, issue: &PosIssue, fix: PosFix) -> Result<PathBuf, String> {
+    rewrite_json(path, |value| {
+        let objective = named_objective(path, value, issue.index, &issue.name)?;
+
+        match fix {
+            PosFix::PadOrTruncate => {
+                let axes = objective
+                    .get("pos")
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default();
+                let fixed: Vec<Value> = (0..3)
+                    .map(|i| match axes.get(i).and_then(Value::as_f64) {
+                        Some(axis) if axis.is_finite() => axes[i].clone(),
+                        _ => Value::from(0),
+                    })
+                    .collect();
+                objective.insert("pos".to_string(), Value::Array(fixed));
+            }
+            PosFix::Remove => {
+                objective.remove("pos");
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Appends `objective` to the `objectives` array of `path`, creating the array if it is
+/// missing or null, and returns the backup path
+#[cfg_attr(not(feature = "mumble-link"), allow(dead_code))]
+pub fn append_objective(path: &Path, objective: Value) -> Result<PathBuf, String> {
+    rewrite_json(path, |value| push_objective(path, value, objective))
+}
+
+/// Copies objective `index` of `from`, exactly as written there, to the end of `to` and
+/// returns the backup path of `to`; fails if `to` already has an identical objective
+pub fn copy_objective(from: &Path, index: usize, to: &Path) -> Result<PathBuf, String> {
+    let content = data::read_data_file(from)
+        .map_err(|e| format!("Could not read {}: {}", from.display(), e))?;
+    let mut value: Value = serde_json::from_str(data::strip_bom(&content).0)
+        .map_err(|e| format!("Could not parse {}: {}", from.display(), e))?;
+    let objective = objectives_mut(&mut value)
+        .and_then(|objectives| objectives.get_mut(index))
+        .map(Value::take)
+        .ok_or_else(|| format!("{} changed since it was loaded", from.display()))?;
+    rewrite_json(to, |value| {
+        if objectives_mut(value).is_some_and(|objectives| objectives.contains(&objective)) {
+            return Err(format!("{} already has this objective", to.display()));
+        }
+        push_objective(to, value, objective)
+    })
+}
+
+/// Appends `objective` to the objectives of the data file `value` read from `path`
+fn push_objective(path: &Path, value: &mut Value, objective: Value) -> Result<(), String> {
+    if let Value::Array(objectives) = value {
+        objectives.push(objective);
+        return Ok(());
+    }
+    let file = value
+        .as_object_mut()
+        .ok_or_else(|| format!("{} is not a JSON object", path.display()))?;
+    let objectives = file.entry("objectives").or_insert(Value::Null);
+    if objectives.is_null() {
+        *objectives = Value::Array(Vec::new());
+    }
+    objectives
+        .as_array_mut()
+        .ok_or_else(|| format!("{} has no objectives list", path.display()))?
+        .push(objective);
+    Ok(())
+}
+
+/// Rewrites the `objectives` array of `path` in `order`, a list of indices into the current
+/// array, returning the backup path
+pub fn reorder_objectives(path: &Path, order: &[usize]) -> Result<PathBuf, String> {
+    rewrite_json(path, |value| {
+        let objectives = objectives_mut(value)
+            .ok_or_else(|| format!("{} has no objectives list", path.display()))?;
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+        if !sorted.iter().copied().eq(0..objectives.len()) {
+            return Err(format!("{} changed since it was loaded", path.display()));
+        }
+        *objectives = order.iter().map(|&index| objectives[index].clone()).collect();
+        Ok(())
+    })
+}
+
+/// Sets the hint of objective `index` in `path`, removing it when `hint` is empty, and
+/// returns the backup path; fails if that objective is no longer named `name`
+pub fn set_hint(path: &Path, index: usize, name: &str, hint: &str) -> Result<PathBuf, String> {
+    rewrite_json(path, |value| {
+        let objective = named_objective(path, value, index, name)?;
+        if hint.is_empty() {
+            objective.remove("hint");
+        } else {
+            objective.insert("hint".to_string(), Value::from(hint));
+        }
+        Ok(())
+    })
+}
+
+/// Opens `path` with `command`, after replacing `{file}` and `{line}` in its arguments;
+/// an empty command opens the file with the default app, which cannot jump to the line
+pub fn open_in_editor(command: &str, path: &Path, line: usize) -> Result<(), String> {
+    let file = path.display().to_string();
+    let line = line.to_string();
+    let mut args = command
+        .split_whitespace()
+        .map(|arg| arg.replace("{file}", &file).replace("{line}", &line));
+    match args.next() {
+        Some(program) => Command::new(&program)
+            .args(args)
+            .spawn()
+            .map(drop)
+            .map_err(|e| format!("Could not run {}: {}", program, e)),
+        None => open::that_detached(path).map_err(|e| format!("Could not open {}: {}", file, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::TempDir;
+    use serde_json::json;
+
+    /// Writes `value` to `name` in `dir` and returns the path
+    fn write(dir: &Path, name: &str, value: &Value) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, value.to_string()).unwrap();
+        path
+    }
+
+    fn read(path: &Path) -> Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn issue(index: usize, name: &str) -> PosIssue {
+        PosIssue {
+            index,
+            name: name.to_string(),
+            pos: Value::Null,
+            line: None,
+        }
+    }
+
+    #[test]
+    fn backups_take_the_first_free_name() {
+        let dir = TempDir::new("edit-backup");
+        let path = write(&dir, "a.json", &json!({ "objectives": [] }));
+        assert_eq!(backup_file(&path).unwrap(), dir.join("a.json.bak"));
+        assert_eq!(backup_file(&path).unwrap(), dir.join("a.json.bak2"));
+        assert_eq!(read(&dir.join("a.json.bak2")), json!({ "objectives": [] }));
+    }
+
+    #[test]
+    fn fixes_positions() {
+        let dir = TempDir::new("edit-fix-pos");
+        let objectives = json!([{ "name": "A", "pos": [1, "a"] }, { "name": "B", "pos": "x" }]);
+        let path = write(&dir, "a.json", &json!({ "objectives": objectives }));
+
+        let backup = fix_pos(&path, &issue(0, "A"), PosFix::PadOrTruncate).unwrap();
+        assert_eq!(read(&backup)["objectives"], objectives);
+        fix_pos(&path, &issue(1, "B"), PosFix::Remove).unwrap();
+        let expected = json!([{ "name": "A", "pos": [1, 0, 0] }, { "name": "B" }]);
+        assert_eq!(read(&path)["objectives"], expected);
+
+        let error = fix_pos(&path, &issue(1, "A"), PosFix::Remove).unwrap_err();
+        assert!(error.ends_with("changed since it was loaded"), "{}", error);
+    }
+
+    #[test]
+    fn edits_bare_array_files() {
+        let dir = TempDir::new("edit-bare-array");
+        let path = write(&dir, "a.json", &json!([{ "name": "A", "pos": [1, 2, 3, 4] }]));
+        fix_pos(&path, &issue(0, "A"), PosFix::PadOrTruncate).unwrap();
+        append_objective(&path, json!({ "name": "B" })).unwrap();
+        assert_eq!(read(&path), json!([{ "name": "A", "pos": [1, 2, 3] }, { "name": "B" }]));
+    }
+
+    #[test]
+    fn copies_objectives_into_null_lists_once() {
+        let dir = TempDir::new("edit-copy");
+        let from = write(&dir, "from.json", &json!([{ "name": "A" }, { "name": "B" }]));
+        let to = write(&dir, "to.json", &json!({ "name": "To", "objectives": null }));
+
+        copy_objective(&from, 1, &to).unwrap();
+        assert_eq!(read(&to)["objectives"], json!([{ "name": "B" }]));
+        let error = copy_objective(&from, 1, &to).unwrap_err();
+        assert!(error.ends_with("already has this objective"), "{}", error);
+        assert!(copy_objective(&from, 2, &to).is_err());
+    }
+
+    #[test]
+    fn reorders_objectives() {
+        let dir = TempDir::new("edit-reorder");
+        let objectives = json!([{ "name": "A" }, { "name": "B" }, { "name": "C" }]);
+        let path = write(&dir, "a.json", &json!({ "objectives": objectives }));
+
+        reorder_objectives(&path, &[2, 0, 1]).unwrap();
+        let expected = json!([{ "name": "C" }, { "name": "A" }, { "name": "B" }]);
+        assert_eq!(read(&path)["objectives"], expected);
+        for stale in [&[0, 1][..], &[0, 1, 1], &[0, 1, 2, 3]] {
+            assert!(reorder_objectives(&path, stale).is_err(), "{:?}", stale);
+        }
+        assert_eq!(read(&path)["objectives"], expected);
+    }
+
+    #[test]
+    fn sets_and_removes_hints() {
+        let dir = TempDir::new("edit-hint");
+        let path = write(&dir, "a.json", &json!({ "objectives": [{ "name": "A" }] }));
+
+        set_hint(&path, 0, "A", "Jump").unwrap();
+        assert_eq!(read(&path)["objectives"], json!([{ "name": "A", "hint": "Jump" }]));
+        set_hint(&path, 0, "A", "").unwrap();
+        assert_eq!(read(&path)["objectives"], json!([{ "name": "A" }]));
+        assert!(set_hint(&path, 0, "B", "Jump").is_err());
+    }
+}
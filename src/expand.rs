@@ -0,0 +1,154 @@
+use imgui::{IdStackToken, Ui};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Seconds a tree node takes to open or close
+const DURATION: f32 = 0.15;
+
+/// Time after which an animating node that is no longer drawn is forgotten
+const STALE: Duration = Duration::from_secs(1);
+
+/// A tree node opening or closing
+struct Animation {
+    /// 0 when closed, 1 when open
+    progress: f32,
+    opening: bool,
+    /// Height of the node's contents when they were last drawn
+    height: f32,
+    /// When the node was last drawn, to drop nodes that are no longer shown
+    drawn: Instant,
+}
+
+/// Height animation of tree nodes opened or closed by the user: their contents are clipped
+/// to a height that grows or shrinks over a few frames, and the nodes below move along
+#[derive(Default)]
+pub struct Expansions {
+    animations: HashMap<NodeId, Animation>,
+}
+
+/// ID of a tree node, as returned by `Expansions::track`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(u32);
+
+/// Contents of a tree node being drawn, returned by `Expansions::begin`
+pub struct Reveal<'ui> {
+    /// Set while the node animates
+    id: Option<NodeId>,
+    /// Cursor position where the contents start, in window coordinates
+    start: [f32; 2],
+    /// The node's ID scope and indent while it draws its contents after being closed
+    closing: Option<IdStackToken<'ui>>,
+}
+
+impl Expansions {
+    /// True while a node is opening or closing, so frames keep coming
+    pub fn is_animating(&self) -> bool {
+        self.animations.values().any(|animation| animation.drawn.elapsed() < STALE)
+    }
+
+    /// Notes whether the tree node just drawn is `open`, starting to animate it if the user
+    /// toggled it and `animate` is set, and returns its ID for `begin`
+    pub fn track(&mut self, ui: &Ui, open: bool, animate: bool) -> NodeId {
+        // SAFETY: only reads the ID of the last item drawn
+        let id = NodeId(unsafe { imgui::sys::igGetItemID() });
+        let now = Instant::now();
+        self.animations.retain(|_, animation| now - animation.drawn < STALE);
+        if animate && ui.is_item_toggled_open() {
+            let (progress, height) = match self.animations.get(&id) {
+                Some(animation) => (animation.progress, animation.height),
+                // A closing node is measured on its first frame, an opening one starts empty
+                None if open => (0.0, 0.0),
+                None => (1.0, f32::INFINITY),
+            };
+            let animation = Animation {
+                progress,
+                opening: open,
+                height,
+                drawn: now,
+            };
+            self.animations.insert(id, animation);
+        }
+        // Opened or closed by something else, such as a search, which does not animate
+        match self.animations.get_mut(&id) {
+            Some(animation) if animation.opening != open => {
+                self.animations.remove(&id);
+            }
+            Some(animation) => animation.drawn = now,
+            None => {}
+        }
+        id
+    }
+
+    /// Starts the contents of the tree node `id` labelled `label`, tracked as `open` this
+    /// frame. Returns `None` when they are hidden; otherwise they are drawn, then passed to
+    /// `end`.
+    pub fn begin<'ui>(
+        &mut self,
+        ui: &'ui Ui,
+        id: NodeId,
+        label: &str,
+        open: bool,
+    ) -> Option<Reveal<'ui>> {
+        let Some(animation) = self.animations.get_mut(&id) else {
+            return open.then(|| Reveal {
+                id: None,
+                start: ui.cursor_pos(),
+                closing: None,
+            });
+        };
+
+        let step = ui.io().delta_time / DURATION;
+        let direction = if animation.opening { step } else { -step };
+        animation.progress = (animation.progress + direction).clamp(0.0, 1.0);
+        let closing = (!open).then(|| {
+            // Tree node IDs come from the part of the label after `###`, as in `tree_node`
+            let token = ui.push_id(format!("###{}", label));
+            ui.indent();
+            token
+        });
+        let start = ui.cursor_pos();
+        let min = ui.cursor_screen_pos();
+        let shown = animation.height * ease(animation.progress);
+        let max = [ui.window_pos()[0] + ui.window_size()[0], min[1] + shown];
+        let min = [ui.window_pos()[0], min[1]];
+        // SAFETY: pushes a clip rectangle on the current window, popped again in `end`
+        unsafe {
+            imgui::sys::igPushClipRect(min.into(), max.into(), true);
+        }
+        Some(Reveal {
+            id: Some(id),
+            start,
+            closing,
+        })
+    }
+
+    /// Ends the contents started by `begin`, moving the cursor to the animated height
+    pub fn end(&mut self, ui: &Ui, reveal: Reveal) {
+        let Some(id) = reveal.id else {
+            return;
+        };
+        // SAFETY: pops the clip rectangle pushed in `begin`
+        unsafe {
+            imgui::sys::igPopClipRect();
+        }
+        if let Some(animation) = self.animations.get_mut(&id) {
+            animation.height = ui.cursor_pos()[1] - reveal.start[1];
+            let shown = animation.height * ease(animation.progress);
+            ui.set_cursor_pos([ui.cursor_pos()[0], reveal.start[1] + shown]);
+            let target = if animation.opening { 1.0 } else { 0.0 };
+            if animation.progress == target {
+                self.animations.remove(&id);
+            }
+        }
+        if reveal.closing.is_some() {
+            ui.unindent();
+        }
+    }
+}
+
+/// Eases `progress` in and out, so nodes start and stop moving gently
+fn ease(progress: f32) -> f32 {
+    progress * progress * (3.0 - 2.0 * progress)
+}
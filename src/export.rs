@@ -0,0 +1,73 @@
+use crate::{config::Config, data::Position};
+
+/// Escapes a value for use inside a Markdown table cell, putting line breaks on one line
+pub fn escape_cell(value: &str) -> String {
+    // Backslashes first, so the ones escaping pipes are not escaped again
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Renders objectives as a Markdown table with Name, Map, the coordinates in `axis_order`
+/// and Hint columns
+pub fn markdown_table<'a>(
+    objectives: impl IntoIterator<Item = &'a Position>,
+    config: &Config,
+) -> String {
+    let [x, y, z] = config.axis_labels();
+    let mut table = format!("| Name | Map | {} | {} | {} | Hint |\n", x, y, z);
+    table.push_str("|---|---|---|---|---|---|\n");
+    for objective in objectives {
+        let map = objective.map.map(|map| map.to_string()).unwrap_or_default();
+        let [x, y, z] = match objective.pos {
+            Some(pos) => config.format_axes(pos),
+            None => Default::default(),
+        };
+        let hint = objective.hint.as_deref().map(escape_cell).unwrap_or_default();
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            escape_cell(&objective.name),
+            map,
+            x,
+            y,
+            z,
+            hint
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_cells() {
+        assert_eq!(escape_cell("a|b"), "a\\|b");
+        assert_eq!(escape_cell("ends in \\"), "ends in \\\\");
+        assert_eq!(escape_cell("one\r\ntwo\rthree\nfour"), "one two three four");
+    }
+
+    #[test]
+    fn renders_markdown_table() {
+        let objectives: Vec<Position> = serde_json::from_str(
+            r#"[
+                { "name": "A|B", "map": 15, "pos": [1, 2, 3], "hint": "Up\r\nthen left" },
+                { "name": "C" }
+            ]"#,
+        )
+        .unwrap();
+        let config = Config {
+            axis_order: "xzy".to_string(),
+            coordinate_precision: 1,
+            ..Config::default()
+        };
+        let expected = "| Name | Map | X | Z | Y | Hint |\n\
+            |---|---|---|---|---|---|\n\
+            | A\\|B | 15 | 1.0 | 3.0 | 2.0 | Up then left |\n\
+            | C |  |  |  |  |  |\n";
+        assert_eq!(markdown_table(&objectives, &config), expected);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::data::ObjectiveKey;
+use std::{collections::BTreeSet, fs, path::PathBuf};
+
+/// Sidecar file the favorites are persisted to
+const FAVORITES_PATH: &str = "favorites.json";
+
+/// Objectives the user starred, persisted to `favorites.json`
+#[derive(Default)]
+pub struct Favorites {
+    keys: BTreeSet<ObjectiveKey>,
+    /// File the favorites are saved to; unset for favorites that are not persisted
+    path: Option<PathBuf>,
+    /// Counts the changes, so lists built from the favorites notice when they are stale
+    revision: u64,
+}
+
+impl Favorites {
+    /// Loads the favorites file, starting empty if it is missing or invalid
+    pub fn load() -> Self {
+        let keys = match fs::read_to_string(FAVORITES_PATH) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Error parsing {}: {}", FAVORITES_PATH, e);
+                BTreeSet::new()
+            }),
+            Err(_) => BTreeSet::new(),
+        };
+        Favorites {
+            keys,
+            path: Some(PathBuf::from(FAVORITES_PATH)),
+            revision: 0,
+        }
+    }
+
+    /// Writes the favorites file
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&self.keys)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Error saving {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: &ObjectiveKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Returns every favorite in sorted order
+    pub fn keys(&self) -> impl Iterator<Item = &ObjectiveKey> {
+        self.keys.iter()
+    }
+
+    /// Adds imported favorites, or uses only those if `replace` is set, and saves
+    pub fn merge(&mut self, keys: impl IntoIterator<Item = ObjectiveKey>, replace: bool) {
+        if replace {
+            self.keys.clear();
+        }
+        self.keys.extend(keys);
+        self.revision += 1;
+        self.save();
+    }
+
+    /// Stars or unstars an objective and saves the change
+    pub fn toggle(&mut self, key: ObjectiveKey) {
+        if !self.keys.remove(&key) {
+            self.keys.insert(key);
+        }
+        self.revision += 1;
+        self.save();
+    }
+}
@@ -0,0 +1,75 @@
+use imgui::{StyleColor, Ui};
+
+/// Small icon drawn before a tree entry to tell the kinds of entries apart
+#[derive(Clone, Copy)]
+pub enum Glyph {
+    /// A directory of data files
+    Folder,
+    /// A location file
+    File,
+    /// An objective with a position
+    Pin,
+    /// An objective without a position
+    Point,
+}
+
+/// Spaces to put before a tree node label so a glyph fits between the arrow and the text
+pub fn label_padding(ui: &Ui) -> String {
+    let space = ui.calc_text_size(" ")[0].max(1.0);
+    let width = ui.current_font_size() + ui.clone_style().item_inner_spacing[0];
+    " ".repeat((width / space).ceil() as usize)
+}
+
+/// Draws `glyph` over the padding of the tree node that was just drawn
+pub fn draw_on_tree_node(ui: &Ui, glyph: Glyph) {
+    let min = ui.item_rect_min();
+    let height = ui.item_rect_size()[1];
+    let size = ui.current_font_size();
+    let arrow = size + ui.clone_style().frame_padding[0] * 2.0;
+    draw(ui, glyph, [min[0] + arrow, min[1] + (height - size) / 2.0], size);
+}
+
+/// Draws `glyph` as an item of its own, as tall as a button
+pub fn item(ui: &Ui, glyph: Glyph) {
+    let pos = ui.cursor_screen_pos();
+    let size = ui.current_font_size();
+    let height = ui.frame_height();
+    ui.dummy([size, height]);
+    draw(ui, glyph, [pos[0], pos[1] + (height - size) / 2.0], size);
+}
+
+/// Draws `glyph` in a square of `size` pixels with its top-left corner at `pos`
+fn draw(ui: &Ui, glyph: Glyph, pos: [f32; 2], size: f32) {
+    let draw_list = ui.get_window_draw_list();
+    let color = ui.style_color(StyleColor::Text);
+    let at = |x: f32, y: f32| [pos[0] + x * size, pos[1] + y * size];
+    match glyph {
+        Glyph::Folder => {
+            draw_list.add_rect(at(0.05, 0.15), at(0.45, 0.35), color).filled(true).build();
+            draw_list
+                .add_rect(at(0.05, 0.3), at(0.95, 0.85), color)
+                .filled(true)
+                .rounding(size * 0.08)
+                .build();
+        }
+        Glyph::File => {
+            draw_list.add_rect(at(0.2, 0.05), at(0.8, 0.95), color).thickness(1.0).build();
+            for y in [0.35, 0.55, 0.75] {
+                draw_list.add_line(at(0.32, y), at(0.68, y), color).build();
+            }
+        }
+        Glyph::Pin => {
+            draw_list
+                .add_triangle(at(0.25, 0.45), at(0.75, 0.45), at(0.5, 0.95), color)
+                .filled(true)
+                .build();
+            draw_list.add_circle(at(0.5, 0.38), size * 0.27, color).filled(true).build();
+            let hole = ui.style_color(StyleColor::WindowBg);
+            draw_list.add_circle(at(0.5, 0.38), size * 0.1, hole).filled(true).build();
+        }
+        Glyph::Point => {
+            let color = ui.style_color(StyleColor::TextDisabled);
+            draw_list.add_circle(at(0.5, 0.5), size * 0.2, color).thickness(1.0).build();
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::data::DataTree;
+use std::{
+    path::Path,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+use tiny_http::{Header, Method, Response, Server};
+
+/// A call received by the HTTP server, answered on the UI thread
+pub enum ApiCall {
+    /// `GET /locations`
+    Locations,
+    /// `POST /select` with a `{"name": ...}` body
+    Select { name: String },
+}
+
+/// An API call together with the channel its `(status, JSON body)` reply is sent on
+pub struct ApiRequest {
+    pub call: ApiCall,
+    pub reply: Sender<(u16, String)>,
+}
+
+/// Starts the server on `127.0.0.1:port` and returns the channel its calls arrive on; `wake`
+/// is called after each call is sent, so an idle UI thread gets to answer it
+pub fn start(port: u16, wake: impl Fn() + Send + 'static) -> Result<Receiver<ApiRequest>, String> {
+    let address = format!("127.0.0.1:{}", port);
+    let server =
+        Server::http(&address).map_err(|e| format!("Could not listen on {}: {}", address, e))?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            serve(request, &sender, &wake);
+        }
+    });
+    Ok(receiver)
+}
+
+/// Parses one HTTP request, forwards it to the UI thread and writes the reply
+fn serve(mut request: tiny_http::Request, sender: &Sender<ApiRequest>, wake: &dyn Fn()) {
+    let url = request.url();
+    let path = url.split_once('?').map_or(url, |(path, _)| path);
+    let call = match (request.method(), path) {
+        (Method::Get, "/locations") => Ok(ApiCall::Locations),
+        // Web pages can only send JSON after a CORS preflight, which is never answered, so
+        // other pages the user has open cannot select objectives
+        (Method::Post, "/select") if !is_json(&request) => Err((
+            415,
+            "Expected a Content-Type: application/json body".to_string(),
+        )),
+        (Method::Post, "/select") => {
+            let mut body = String::new();
+            match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => parse_select(&body),
+                Err(e) => Err((400, e.to_string())),
+            }
+        }
+        _ => Err((404, "Not found".to_string())),
+    };
+
+    let (status, body) = match call {
+        Ok(call) => {
+            let (reply, replied) = mpsc::channel();
+            sender
+                .send(ApiRequest { call, reply })
+                .ok()
+                .and_then(|_| {
+                    wake();
+                    replied.recv().ok()
+                })
+                .unwrap_or_else(|| (503, error_body("The app is shutting down")))
+        }
+        Err((status, message)) => (status, error_body(&message)),
+    };
+
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Error answering HTTP request: {}", e);
+    }
+}
+
+/// Returns true if the request says its body is JSON, ignoring parameters such as `charset`
+fn is_json(request: &tiny_http::Request) -> bool {
+    request.headers().iter().any(|header| {
+        let value = header.value.as_str();
+        let media_type = value.split(';').next().unwrap_or_default().trim();
+        header.field.equiv("Content-Type") && media_type.eq_ignore_ascii_case("application/json")
+    })
+}
+
+/// Reads the objective name out of a `POST /select` body
+fn parse_select(body: &str) -> Result<ApiCall, (u16, String)> {
+    let value: serde_json::Value = serde_json::from_str(body).map_err(|e| (400, e.to_string()))?;
+    match value.get("name").and_then(|name| name.as_str()) {
+        Some(name) => Ok(ApiCall::Select { name: name.to_string() }),
+        None => Err((400, "Expected a JSON body with a \"name\" string".to_string())),
+    }
+}
+
+/// Serializes every loaded location for `GET /locations`, with the number of files that
+/// are not loaded yet
+pub fn locations_body(tree: &DataTree, data_dir: &Path) -> String {
+    let locations: Vec<_> = tree
+        .locations()
+        .into_iter()
+        .map(|location| {
+            let objectives: Vec<_> = location
+                .data
+                .objectives
+                .iter()
+                .map(|objective| {
+                    let mut value = serde_json::to_value(objective).unwrap_or_default();
+                    // Left out when writing data files, but a client can still load the image
+                    if let (Some(icon), Some(value)) = (&objective.icon, value.as_object_mut()) {
+                        value.insert("icon".to_string(), serde_json::json!(icon));
+                    }
+                    value
+                })
+                .collect();
+            serde_json::json!({
+                "file": location.path.strip_prefix(data_dir).unwrap_or(&location.path),
+                "name": location.data.name,
+                "objectives": objectives,
+            })
+        })
+        .collect();
+    serde_json::json!({ "locations": locations, "pending": tree.summary.pending }).to_string()
+}
+
+/// Wraps a message as `{"error": ...}`
+pub fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
@@ -0,0 +1,71 @@
+use glium::{texture::RawImage2d, uniforms::SamplerBehavior, Texture2d};
+use imgui::TextureId;
+use imgui_glium_renderer::{Renderer, Texture};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Objective icons uploaded as imgui textures, loaded once per path
+#[derive(Default)]
+pub struct IconCache {
+    /// `None` marks files that are missing or failed to decode
+    textures: HashMap<PathBuf, Option<TextureId>>,
+    /// Paths requested during the frame, uploaded by `load_pending`
+    pending: Vec<PathBuf>,
+    /// Textures dropped by `clear`, freed by `load_pending`
+    stale: Vec<TextureId>,
+}
+
+impl IconCache {
+    /// Returns the texture of the icon at `path`, queueing it if it was not loaded yet
+    pub fn get(&mut self, path: &Path) -> Option<TextureId> {
+        match self.textures.get(path) {
+            Some(texture) => *texture,
+            None => {
+                if !self.pending.iter().any(|pending| pending == path) {
+                    self.pending.push(path.to_path_buf());
+                }
+                None
+            }
+        }
+    }
+
+    /// Uploads the icons requested since the last call; needs the renderer, so runs after the
+    /// frame. Returns true if any were requested, to be drawn in another frame.
+    pub fn load_pending(&mut self, display: &glium::Display, renderer: &mut Renderer) -> bool {
+        let loaded = !self.pending.is_empty();
+        for texture in self.stale.drain(..) {
+            renderer.textures().remove(texture);
+        }
+        for path in self.pending.drain(..) {
+            let texture = match load_texture(display, &path) {
+                Ok(texture) => Some(renderer.textures().insert(texture)),
+                Err(e) => {
+                    eprintln!("Error loading icon {}: {}", path.display(), e);
+                    None
+                }
+            };
+            self.textures.insert(path, texture);
+        }
+        loaded
+    }
+
+    /// Forgets every icon so edited files are picked up again
+    pub fn clear(&mut self) {
+        self.stale.extend(self.textures.drain().filter_map(|(_, texture)| texture));
+    }
+}
+
+/// Decodes an image file into a texture
+fn load_texture(display: &glium::Display, path: &Path) -> Result<Texture, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?.into_rgba8();
+    let dimensions = image.dimensions();
+    let raw = RawImage2d::from_raw_rgba(image.into_raw(), dimensions);
+    let texture = Texture2d::new(display, raw).map_err(|e| e.to_string())?;
+    Ok(Texture {
+        texture: Rc::new(texture),
+        sampler: SamplerBehavior::default(),
+    })
+}
@@ -0,0 +1,173 @@
+use imgui::{Key, Ui};
+
+/// Something a keyboard shortcut triggers
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    CopyObjective,
+    OpenDirectory,
+    ToggleCheatsheet,
+    ToggleFps,
+}
+
+/// How a keybinding is detected
+pub enum Trigger {
+    /// Checked by `pressed`
+    Chord {
+        action: Action,
+        key: Key,
+        ctrl: bool,
+        shift: bool,
+    },
+    /// Handled by imgui navigation or type-ahead, listed for the cheatsheet only
+    Builtin,
+}
+
+/// A keyboard shortcut as registered and as listed in the cheatsheet
+pub struct Keybinding {
+    /// Keys as shown in the cheatsheet
+    pub keys: &'static str,
+    pub description: &'static str,
+    pub trigger: Trigger,
+}
+
+/// Every keyboard shortcut of the app
+pub const KEYBINDINGS: &[Keybinding] = &[
+    Keybinding {
+        keys: "F1",
+        description: "Show or hide this list",
+        trigger: Trigger::Chord {
+            action: Action::ToggleCheatsheet,
+            key: Key::F1,
+            ctrl: false,
+            shift: false,
+        },
+    },
+    Keybinding {
+        keys: "?",
+        description: "Show or hide this list",
+        trigger: Trigger::Chord {
+            action: Action::ToggleCheatsheet,
+            key: Key::Slash,
+            ctrl: false,
+            shift: true,
+        },
+    },
+    Keybinding {
+        keys: "F3",
+        description: "Show or hide the frame time overlay",
+        trigger: Trigger::Chord {
+            action: Action::ToggleFps,
+            key: Key::F3,
+            ctrl: false,
+            shift: false,
+        },
+    },
+    Keybinding {
+        keys: "Ctrl+O",
+        description: "Open another data directory",
+        trigger: Trigger::Chord {
+            action: Action::OpenDirectory,
+            key: Key::O,
+            ctrl: true,
+            shift: false,
+        },
+    },
+    Keybinding {
+        keys: "Arrow keys",
+        description: "Move between objectives and locations",
+        trigger: Trigger::Builtin,
+    },
+    Keybinding {
+        keys: "Enter / Space",
+        description: "Select the focused objective or open the focused location",
+        trigger: Trigger::Builtin,
+    },
+    Keybinding {
+        keys: "Ctrl+C",
+        description: "Copy the focused objective's coordinates",
+        trigger: Trigger::Chord {
+            action: Action::CopyObjective,
+            key: Key::C,
+            ctrl: true,
+            shift: false,
+        },
+    },
+    Keybinding {
+        keys: "Escape",
+        description: "Clear the search, or leave the search box once it is empty",
+        trigger: Trigger::Builtin,
+    },
+    Keybinding {
+        keys: "Letters",
+        description: "Jump to the next entry starting with the typed text",
+        trigger: Trigger::Builtin,
+    },
+];
+
+/// Returns true if a chord bound to `action` was pressed this frame
+pub fn pressed(ui: &Ui, action: Action) -> bool {
+    let io = ui.io();
+    KEYBINDINGS.iter().any(|binding| match binding.trigger {
+        Trigger::Chord {
+            action: bound,
+            key,
+            ctrl,
+            shift,
+        } => {
+            bound == action
+                && io.key_ctrl == ctrl
+                && io.key_shift == shift
+                && ui.is_key_pressed_no_repeat(key)
+        }
+        Trigger::Builtin => false,
+    })
+}
+
+/// Parses a chord written like `F4` or `Ctrl+Shift+E` into its key and its Ctrl and Shift
+/// modifiers; key names are imgui's, ignoring case
+pub fn parse_chord(text: &str) -> Option<(Key, bool, bool)> {
+    let (mut ctrl, mut shift) = (false, false);
+    let mut parts: Vec<_> = text.split('+').map(str::trim).collect();
+    let name = parts.pop()?;
+    for modifier in parts {
+        match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" => ctrl = true,
+            "shift" => shift = true,
+            _ => return None,
+        }
+    }
+    let key = Key::VARIANTS
+        .iter()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))?;
+    Some((*key, ctrl, shift))
+}
+
+/// Returns true if the chord written in `text`, as read by `parse_chord`, was pressed this
+/// frame
+pub fn chord_pressed(ui: &Ui, text: &str) -> bool {
+    let io = ui.io();
+    parse_chord(text).is_some_and(|(key, ctrl, shift)| {
+        io.key_ctrl == ctrl && io.key_shift == shift && ui.is_key_pressed_no_repeat(key)
+    })
+}
+
+/// Returns true if any key or mouse button was pressed this frame
+pub fn any_pressed(ui: &Ui) -> bool {
+    Key::VARIANTS.iter().any(|&key| ui.is_key_pressed_no_repeat(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chords() {
+        assert_eq!(parse_chord("F2"), Some((Key::F2, false, false)));
+        assert_eq!(parse_chord("Ctrl+Shift+F"), Some((Key::F, true, true)));
+        assert_eq!(parse_chord(" shift + ctrl+f "), Some((Key::F, true, true)));
+        assert_eq!(parse_chord("ctrl+pageup"), Some((Key::PageUp, true, false)));
+        for invalid in ["", "Ctrl+", "Alt+F", "Ctrl+Nope", "F2+Ctrl"] {
+            assert_eq!(parse_chord(invalid), None, "{:?}", invalid);
+        }
+    }
+}
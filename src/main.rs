@@ -1,108 +1,2568 @@
+mod cli;
+mod clipboard;
+mod compare;
+mod config;
+mod crash;
+mod csv_import;
+mod data;
+mod diff;
+mod edit;
+mod expand;
+mod export;
+mod favorites;
+mod glyphs;
+#[cfg(feature = "http-server")]
+mod http;
+mod icons;
+mod keys;
+#[cfg(feature = "mumble-link")]
+mod mumble;
+#[cfg(feature = "mumble-link")]
+mod picker;
+mod profile;
+mod queue;
+mod raw_json;
+mod report;
+mod search;
+mod selection;
+mod selection_log;
+mod settings;
+mod usage;
+
+use clipboard::Clipboard;
+use config::{Activation, Config, ConfigWatcher, SortMode, ViewMode, WindowMode};
+use data::{DataTree, LoadError, Location, Node, ObjectiveKey, Position, SourceLine};
+use edit::PosFix;
+use favorites::Favorites;
+use glyphs::Glyph;
 use glium::{glutin, Surface};
 use imgui::{Context, Ui};
 use imgui_glium_renderer::Renderer;
 use imgui_winit_support::{HiDpiMode, WinitPlatform};
-use serde::Deserialize;
-use std::{fs, path::PathBuf, time::Instant};
+use keys::Action;
+use regex::Regex;
+use search::{Match, RangeFilter, Search};
+use settings::SettingsWindow;
+use usage::Usage;
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// File the window layout is kept in when `save_layout` is on
+const LAYOUT_PATH: &str = "imgui.ini";
+
+/// Drag and drop payload type of objectives being reordered
+const REORDER_PAYLOAD: &str = "reorder_objective";
+
+/// Color of the marker on locations whose file changed since the last run
+const CHANGED_COLOR: [f32; 4] = [0.45, 0.8, 0.45, 1.0];
+
+/// UI time per frame above which debug builds warn, about one frame at 60 Hz
+#[cfg(debug_assertions)]
+const FRAME_BUDGET: Duration = Duration::from_millis(16);
+
+/// Frames drawn after each input, so imgui settles hover and click state before the loop idles
+const FRAMES_AFTER_INPUT: u32 = 3;
+
+/// Longest the event loop sleeps while idle; the config file and the game's map are checked
+/// this often
+const IDLE_WAKE: Duration = Duration::from_secs(1);
+
+/// How long the errors panel's shortcut hint flashes after new errors appear
+const ERRORS_FLASH: Duration = Duration::from_secs(3);
+
+/// Idle time after which the type-to-select prefix starts over
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// Tracks the prefix typed while the tree is focused, used to jump to nodes
+#[derive(Default)]
+struct TypeAhead {
+    prefix: String,
+    last_input: Option<Instant>,
+    /// Set when the prefix changed this frame and no node has been focused for it yet
+    pending: bool,
+}
+
+impl TypeAhead {
+    /// Collects the characters typed this frame into the prefix
+    fn update(&mut self, ui: &Ui) {
+        // A prefix no node matched by the end of the frame it was typed in is not looked for
+        // again, which would move the focus once a node with that prefix is drawn
+        self.pending = false;
+        if let Some(last_input) = self.last_input {
+            if last_input.elapsed() > TYPE_AHEAD_TIMEOUT {
+                self.prefix.clear();
+                self.last_input = None;
+            }
+        }
+
+        if ui.io().want_text_input
+            || !ui.is_window_focused_with_flags(imgui::WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS)
+        {
+            return;
+        }
+
+        for c in ui.io().input_queue_characters() {
+            // A leading space is the nav activation key and a leading ? opens the
+            // cheatsheet, neither is part of a name
+            if c.is_control() || ((c == ' ' || c == '?') && self.prefix.is_empty()) {
+                continue;
+            }
+            self.prefix.extend(c.to_lowercase());
+            self.last_input = Some(Instant::now());
+            self.pending = true;
+        }
+    }
+
+    /// Focuses the next item if `name` is the first node matching the prefix
+    fn focus_if_match(&mut self, ui: &Ui, name: &str) {
+        if self.pending && name.to_lowercase().starts_with(&self.prefix) {
+            ui.set_keyboard_focus_here();
+            self.pending = false;
+        }
+    }
+}
+
+/// Objectives of one file dragged into a new order that is not saved yet
+struct Reorder {
+    path: PathBuf,
+    /// Indices into the file's objectives, in the new order
+    order: Vec<usize>,
+}
+
+/// A hint being edited in place, saved to the objective's data file on Enter
+struct HintEdit {
+    source: SourceLine,
+    name: String,
+    text: String,
+    /// Focus the text field on the next frame
+    focus: bool,
+    /// Whether the field was drawn this frame; the edit is dropped once its row is gone
+    drawn: bool,
+}
+
+/// What the flat view's rows are built from besides the tree, to notice when they are stale
+#[derive(PartialEq)]
+struct FlatKey {
+    tree_revision: u64,
+    query: String,
+    include_hints: bool,
+    scope: Option<PathBuf>,
+    show_hidden: bool,
+    changed_only: bool,
+    /// Revision of the favorites while only favorites are listed
+    favorites: Option<u64>,
+    /// Map the objectives are limited to, if other maps are hidden
+    map: Option<i32>,
+    range: RangeFilter,
+    /// Order the range filter's axes are in while it is active
+    axis_order: Option<String>,
+    sort_mode: SortMode,
+    /// Revision of the usage counts while sorting by them
+    usage: Option<u64>,
+    limit: Option<usize>,
+}
+
+/// Rows of the flat view, kept until `key` changes
+struct FlatRows {
+    key: FlatKey,
+    /// Index of each row's location in `DataTree::locations` and of the objective in it,
+    /// with how it matched the search
+    rows: Vec<(usize, usize, Match)>,
+}
+
+/// An objective waiting in the "Copy to another file" dialog
+struct CopyTo {
+    objective: Position,
+    /// Location file picked in the dialog
+    target: Option<PathBuf>,
+}
+
+/// State kept across frames
+struct AppState {
+    config: Config,
+    /// Set by `--quiet`, suppresses selection messages like `config.quiet`
+    quiet: bool,
+    /// Directory the tree is loaded from, the configured or the last opened one
+    data_dir: PathBuf,
+    config_watcher: ConfigWatcher,
+    /// Reload the data directory at the end of the frame
+    reload_requested: bool,
+    last_reload: Instant,
+    type_ahead: TypeAhead,
+    /// Result of the last toolbar or objective action
+    status: Option<String>,
+    /// Result of the last position fix applied from the errors panel
+    fix_status: Option<String>,
+    /// Whether the errors panel was open last frame, for `errors_key` to toggle it
+    errors_open: bool,
+    /// Number of errors last frame, to notice new ones
+    errors_seen: usize,
+    /// When new errors appeared, flashing the `errors_key` hint for a few seconds
+    errors_flash: Option<Instant>,
+    show_hidden: bool,
+    /// Start of the previous session, from `last_launch` before it is updated
+    previous_launch: Option<SystemTime>,
+    /// Only list locations whose file changed since `previous_launch`
+    changed_only: bool,
+    search: Search,
+    /// List every search result regardless of `max_search_results`
+    show_all_results: bool,
+    /// Search results drawn so far this frame
+    results_shown: usize,
+    range: RangeFilter,
+    clipboard: Clipboard,
+    settings: SettingsWindow,
+    cheatsheet_open: bool,
+    /// Open the top-level nodes on the next frame, set after each load
+    expand_top_level: bool,
+    /// Move every window back to its default position and size on the next frame
+    reset_layout: bool,
+    /// Location that stays open in accordion mode, identified by its file path
+    open_location: Option<PathBuf>,
+    /// Selection waiting for the confirmation dialog, with its source path
+    pending_selection: Option<(Position, PathBuf)>,
+    /// Open the confirmation dialog on the next frame
+    confirm_requested: bool,
+    /// Unsaved drag and drop reordering, limited to one file at a time
+    reorder: Option<Reorder>,
+    /// Open the dialog that saves `reorder` on the next frame
+    save_reorder_requested: bool,
+    hint_edit: Option<HintEdit>,
+    copy_to: Option<CopyTo>,
+    /// Open the "Copy to another file" dialog on the next frame
+    copy_to_requested: bool,
+    selection: selection::MultiSelection,
+    /// Objective Shift-clicked this frame, turned into a range once its list is drawn
+    range_clicked: Option<ObjectiveKey>,
+    /// Objective last clicked once and when, for telling double clicks apart
+    last_click: Option<(ObjectiveKey, Instant)>,
+    /// Objective the tree is opened and scrolled to this frame
+    reveal: Option<ObjectiveKey>,
+    /// Directory of the tree node last clicked, offered as the search scope
+    current_dir: Option<PathBuf>,
+    /// Files of a lazy tree opened this frame, parsed at the start of the next one
+    pending_loads: Vec<PathBuf>,
+    /// Files parsed after being opened, kept open once drawn as locations
+    lazily_opened: Vec<PathBuf>,
+    /// Counts the changes to the loaded tree, for `flat_rows`
+    tree_revision: u64,
+    flat_rows: Option<FlatRows>,
+    expansions: expand::Expansions,
+    /// Objective name typed into the debug menu's test selection
+    #[cfg(debug_assertions)]
+    test_selection: String,
+    compare: compare::CompareWindow,
+    queue: queue::QueueWindow,
+    raw_json: raw_json::RawJsonWindow,
+    report: report::ReportWindow,
+    diff: diff::DiffWindow,
+    /// Column and direction (true for ascending) the table view is sorted by
+    table_sort: Option<(usize, bool)>,
+    icons: icons::IconCache,
+    #[cfg(feature = "mumble-link")]
+    picker: picker::PositionPicker,
+    favorites: Favorites,
+    /// Only list favorited objectives
+    favorites_only: bool,
+    usage: Usage,
+    /// Map the player is on, 0 when unknown
+    current_map: i32,
+    /// Hide objectives on other maps instead of graying them out
+    hide_other_maps: bool,
+    /// Keep `current_map` in sync with the running game
+    #[cfg(feature = "mumble-link")]
+    follow_game_map: bool,
+    /// Calls from the HTTP server, if it is running
+    #[cfg(feature = "http-server")]
+    api: Option<std::sync::mpsc::Receiver<http::ApiRequest>>,
+}
+
+impl AppState {
+    fn new(config: Config, args: cli::Args) -> Self {
+        AppState {
+            quiet: args.quiet,
+            data_dir: config.startup_data_dir(),
+            expand_top_level: config.expand_top_level,
+            previous_launch: config
+                .last_launch
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+            config,
+            config_watcher: ConfigWatcher::new(),
+            reload_requested: false,
+            last_reload: Instant::now(),
+            type_ahead: TypeAhead::default(),
+            status: None,
+            fix_status: None,
+            errors_open: false,
+            errors_seen: 0,
+            errors_flash: None,
+            show_hidden: false,
+            changed_only: false,
+            search: Search::default(),
+            show_all_results: false,
+            results_shown: 0,
+            range: RangeFilter::default(),
+            clipboard: Clipboard::default(),
+            settings: SettingsWindow::default(),
+            cheatsheet_open: false,
+            open_location: None,
+            reset_layout: false,
+            pending_selection: None,
+            confirm_requested: false,
+            reorder: None,
+            save_reorder_requested: false,
+            hint_edit: None,
+            copy_to: None,
+            copy_to_requested: false,
+            selection: selection::MultiSelection::default(),
+            range_clicked: None,
+            last_click: None,
+            reveal: None,
+            current_dir: None,
+            pending_loads: Vec::new(),
+            lazily_opened: Vec::new(),
+            tree_revision: 0,
+            flat_rows: None,
+            expansions: expand::Expansions::default(),
+            #[cfg(debug_assertions)]
+            test_selection: String::new(),
+            compare: compare::CompareWindow::default(),
+            queue: queue::QueueWindow::default(),
+            raw_json: raw_json::RawJsonWindow::default(),
+            report: report::ReportWindow::default(),
+            diff: diff::DiffWindow::default(),
+            table_sort: None,
+            icons: icons::IconCache::default(),
+            #[cfg(feature = "mumble-link")]
+            picker: picker::PositionPicker::default(),
+            favorites: Favorites::load(),
+            favorites_only: false,
+            usage: Usage::load(),
+            current_map: 0,
+            hide_other_maps: false,
+            #[cfg(feature = "mumble-link")]
+            follow_game_map: false,
+            #[cfg(feature = "http-server")]
+            api: None,
+        }
+    }
+
+    /// Returns the location's file path relative to the data directory
+    fn source_path<'a>(&self, location: &'a Location) -> &'a Path {
+        location.path.strip_prefix(&self.data_dir).unwrap_or(&location.path)
+    }
+
+    /// Returns true if the favorites filter is on and can apply
+    fn favorites_filter_active(&self) -> bool {
+        self.favorites_only && !self.favorites.is_empty()
+    }
+
+    /// Returns true if `objective` can be used from the current map.
+    /// Objectives without a map and an unknown current map always pass.
+    fn is_on_current_map(&self, objective: &Position) -> bool {
+        self.current_map == 0 || objective.map.is_none_or(|map| map == self.current_map)
+    }
+
+    /// Returns true if the other-maps filter is on and can apply
+    fn map_filter_active(&self) -> bool {
+        self.hide_other_maps && self.current_map != 0
+    }
+
+    /// Returns true if only changed locations are listed
+    fn changed_filter_active(&self) -> bool {
+        self.changed_only && self.previous_launch.is_some()
+    }
+
+    /// Returns true if the location's file was modified after the previous session started
+    fn is_changed(&self, location: &Location) -> bool {
+        location
+            .modified
+            .zip(self.previous_launch)
+            .is_some_and(|(modified, previous)| modified > previous)
+    }
+
+    /// Returns true if `location` passes the changed-files filter
+    fn is_location_visible(&self, location: &Location) -> bool {
+        (!self.changed_filter_active() || self.is_changed(location))
+            && self.search.in_scope(location)
+    }
+
+    /// Returns true while the screen changes without input, so frames have to keep coming
+    fn is_animating(&self) -> bool {
+        self.config.continuous_redraw
+            || self.expansions.is_animating()
+            || self.errors_flash.is_some_and(|start| start.elapsed() < ERRORS_FLASH)
+            || !self.pending_loads.is_empty()
+            || self.reload_requested
+    }
+
+    /// Returns when the event loop has to wake up without input: for the next auto-reload,
+    /// the next step of the teleport queue, the next re-read of truncated files in `tree`,
+    /// or else after `IDLE_WAKE`
+    fn next_wake(&self, tree: &DataTree) -> Instant {
+        let auto_reload = self
+            .config
+            .auto_reload_secs
+            .filter(|secs| *secs > 0)
+            .map(|secs| self.last_reload + Duration::from_secs(secs));
+        let idle = Instant::now() + IDLE_WAKE;
+        [auto_reload, self.queue.next_step_at(), tree.retry_at()]
+            .into_iter()
+            .flatten()
+            .fold(idle, Instant::min)
+    }
+
+    /// Remembers the directory of the tree node last clicked, dropping a search scope it is
+    /// outside of
+    fn set_current_dir(&mut self, dir: &Path) {
+        if self.search.scope.as_ref().is_some_and(|scope| !dir.starts_with(scope)) {
+            self.search.scope = None;
+        }
+        self.current_dir = Some(dir.to_path_buf());
+    }
+
+    /// Returns true if `objective` passes the hidden, favorites, map and range filters
+    fn is_visible(&self, objective: &Position) -> bool {
+        (!objective.hidden || self.show_hidden)
+            && (!self.favorites_filter_active() || self.favorites.contains(&objective.key()))
+            && (!self.map_filter_active() || self.is_on_current_map(objective))
+            && self.range.contains(objective.pos.map(|pos| self.config.output_pos(pos)))
+    }
+
+    /// Returns the number of search results to list, `None` without a query or limit
+    fn result_limit(&self) -> Option<usize> {
+        let limit = self.config.max_search_results;
+        (self.search.is_active() && !self.show_all_results && limit > 0).then_some(limit)
+    }
+
+    /// Counts the search results in `locations`, which are inside a closed node, as shown, so
+    /// `result_limit` covers the same results and "Showing N of M" holds whichever nodes are
+    /// open
+    fn skip_results<'a>(&mut self, locations: impl IntoIterator<Item = &'a Location>) {
+        if self.result_limit().is_none() {
+            return;
+        }
+        let results = locations
+            .into_iter()
+            .filter(|location| self.is_location_visible(location))
+            .flat_map(|location| &location.data.objectives)
+            .filter(|objective| {
+                self.is_visible(objective) && self.search.match_objective(objective).is_some()
+            })
+            .count();
+        self.results_shown += results;
+    }
+
+    /// Returns true once `result_limit` results were drawn this frame
+    fn results_exhausted(&self) -> bool {
+        self.result_limit().is_some_and(|limit| self.results_shown >= limit)
+    }
+
+    /// Returns what the flat view's rows depend on this frame
+    fn flat_key(&self) -> FlatKey {
+        let sort_mode = self.config.sort_mode;
+        FlatKey {
+            tree_revision: self.tree_revision,
+            query: self.search.query.clone(),
+            include_hints: self.search.include_hints,
+            scope: self.search.scope.clone(),
+            show_hidden: self.show_hidden,
+            changed_only: self.changed_filter_active(),
+            favorites: self.favorites_filter_active().then(|| self.favorites.revision()),
+            map: self.map_filter_active().then_some(self.current_map),
+            range: self.range.clone(),
+            axis_order: self.range.is_active().then(|| self.config.axis_order.clone()),
+            sort_mode,
+            usage: (sort_mode == SortMode::MostUsed).then(|| self.usage.revision()),
+            limit: self.result_limit(),
+        }
+    }
+
+    /// Returns true if nodes without visible objectives should be hidden
+    fn is_filtering(&self) -> bool {
+        self.search.is_active()
+            || self.favorites_filter_active()
+            || self.map_filter_active()
+            || self.range.is_active()
+            || self.changed_filter_active()
+    }
+}
+
+/// Copies an objective's chat code and reports the result in the status line
+fn copy_chat_code(state: &mut AppState, objective: &Position, chat_code: &str) {
+    copy_text(state, chat_code, &format!("chat code of {}", objective.name));
+}
+
+/// Copies `text` and reports it in the status line as `what`; prints it instead when
+/// the clipboard is unavailable
+fn copy_text(state: &mut AppState, text: &str, what: &str) {
+    state.status = Some(match state.clipboard.copy(text) {
+        Ok(()) => format!("Copied {}", what),
+        Err(e) => {
+            eprintln!("Error copying to the clipboard: {}", e);
+            println!("{}", text);
+            format!("Clipboard unavailable, printed {} to the terminal", what)
+        }
+    });
+}
+
+/// Runs the configured output for a selected objective
+fn activate_objective(state: &mut AppState, objective: &Position, source: &Path) {
+    state.usage.record(objective.key());
+    state.config.last_selected = Some(objective.key());
+    state.config.save();
+    selection_log::append(&state.config, objective);
+    selection_log::write_last(&state.config, objective);
+    let output_mode = state.config.output_mode;
+    if output_mode.prints() && !state.quiet && !state.config.quiet {
+        match objective.pos {
+            Some(pos) => println!(
+                "Selected position: {} at [{}] from {}",
+                objective.name,
+                state.config.format_output(pos),
+                source.display()
+            ),
+            None => println!(
+                "Selected: {} (no position data) from {}",
+                objective.name, source.display()
+            ),
+        }
+    }
+    if output_mode.copies() {
+        copy_objective(state, objective);
+    }
+}
+
+/// Runs the selection action for a clicked objective, asking first if configured to
+fn select_objective(state: &mut AppState, objective: &Position, source: &Path) {
+    if state.config.confirm_selection {
+        state.pending_selection = Some((objective.clone(), source.to_path_buf()));
+        state.confirm_requested = true;
+    } else {
+        activate_objective(state, objective, source);
+    }
+}
+
+/// Runs the selection action for the first objective named `name` without asking first, even
+/// with `confirm_selection` on; used by the HTTP API, `--select` and the debug menu to drive
+/// the app without a mouse
+fn select_by_name(state: &mut AppState, tree: &DataTree, name: &str) -> Result<(), String> {
+    let (location, objective) = tree.find_objective(name).ok_or_else(|| match tree.summary.pending {
+        0 => format!("No objective named {}", name),
+        pending => format!("No objective named {} ({} files not loaded yet)", name, pending),
+    })?;
+    let source = state.source_path(location);
+    activate_objective(state, objective, source);
+    Ok(())
+}
+
+/// Debug builds' menu for running the selection action by objective name, for QA
+#[cfg(debug_assertions)]
+fn test_selection_menu(ui: &Ui, state: &mut AppState, tree: &DataTree) {
+    ui.input_text("Objective name", &mut state.test_selection).build();
+    if ui.button("Select") {
+        let name = state.test_selection.clone();
+        state.status = Some(match select_by_name(state, tree, &name) {
+            Ok(()) => format!("Test selection of {}", name),
+            Err(e) => e,
+        });
+    }
+}
+
+/// Asks before writing the dragged objective order back to its file
+fn save_reorder_modal(ui: &Ui, state: &mut AppState) {
+    const ID: &str = "Save objective order";
+    if std::mem::take(&mut state.save_reorder_requested) {
+        ui.open_popup(ID);
+    }
+
+    let mut confirmed = None;
+    ui.modal_popup_config(ID).always_auto_resize(true).build(|| {
+        if let Some(reorder) = &state.reorder {
+            ui.text(format!("Save the new order to {}?", reorder.path.display()));
+            ui.text_disabled("The file is rewritten and a backup of it is kept");
+        }
+        if ui.button("Save") {
+            confirmed = Some(true);
+        }
+        ui.same_line();
+        if ui.button("Cancel") {
+            confirmed = Some(false);
+        }
+        if confirmed.is_some() {
+            ui.close_current_popup();
+        }
+    });
+
+    if confirmed == Some(true) {
+        if let Some(reorder) = state.reorder.take() {
+            state.status = Some(match edit::reorder_objectives(&reorder.path, &reorder.order) {
+                Ok(backup) => {
+                    state.reload_requested = true;
+                    format!("Saved {}, backup at {}", reorder.path.display(), backup.display())
+                }
+                Err(e) => {
+                    state.reorder = Some(reorder);
+                    e
+                }
+            });
+        }
+    }
+}
+
+/// Asks which location file a copy of an objective is written to
+fn copy_to_modal(ui: &Ui, state: &mut AppState, tree: &DataTree) {
+    const ID: &str = "Copy to another file";
+    if std::mem::take(&mut state.copy_to_requested) {
+        ui.open_popup(ID);
+    }
+
+    let mut confirmed = None;
+    ui.modal_popup_config(ID).always_auto_resize(true).build(|| {
+        let Some(copy) = &mut state.copy_to else {
+            return;
+        };
+        let Some(source) = &copy.objective.source else {
+            return;
+        };
+        ui.text(format!("Copy {} to:", copy.objective.name));
+        let preview = copy.target.as_ref().map_or_else(
+            || "Pick a location file".to_string(),
+            |target| target.display().to_string(),
+        );
+        if let Some(_combo) = ui.begin_combo("##target", preview) {
+            for location in tree.locations() {
+                if location.path == source.path {
+                    continue;
+                }
+                let selected = copy.target.as_ref() == Some(&location.path);
+                let label = format!("{} ({})", location.data.name, location.path.display());
+                if ui.selectable_config(label).selected(selected).build() {
+                    copy.target = Some(location.path.clone());
+                }
+            }
+        }
+        ui.text_disabled("The file is rewritten and a backup of it is kept");
+        ui.disabled(copy.target.is_none(), || {
+            if ui.button("Copy") {
+                confirmed = Some(true);
+            }
+        });
+        ui.same_line();
+        if ui.button("Cancel") {
+            confirmed = Some(false);
+        }
+        if confirmed.is_some() {
+            ui.close_current_popup();
+        }
+    });
+
+    if confirmed.is_none() {
+        return;
+    }
+    let Some(CopyTo { objective, target }) = state.copy_to.take() else {
+        return;
+    };
+    let (Some(source), Some(target), Some(true)) = (&objective.source, target, confirmed) else {
+        return;
+    };
+    state.status = Some(match edit::copy_objective(&source.path, source.index, &target) {
+        Ok(backup) => {
+            state.reload_requested = true;
+            format!(
+                "Copied {} to {}, backup at {}",
+                objective.name,
+                target.display(),
+                backup.display()
+            )
+        }
+        Err(e) => e,
+    });
+}
+
+/// Shows the "Teleport to X?" dialog for a pending selection
+fn confirmation_modal(ui: &Ui, state: &mut AppState) {
+    const ID: &str = "Confirm teleport";
+    // The key that made the selection may still be down on the frame the dialog opens
+    let just_opened = state.confirm_requested;
+    if just_opened {
+        ui.open_popup(ID);
+        state.confirm_requested = false;
+    }
+    let key_pressed = |key| !just_opened && ui.is_key_pressed_no_repeat(key);
+
+    let mut confirmed = None;
+    let shown = ui.modal_popup_config(ID).always_auto_resize(true).build(|| {
+        if let Some((objective, _)) = &state.pending_selection {
+            ui.text(format!("Teleport to {}?", objective.name));
+        }
+        if ui.button("Teleport") || key_pressed(imgui::Key::Enter) {
+            confirmed = Some(true);
+        }
+        ui.same_line();
+        if ui.button("Cancel") || key_pressed(imgui::Key::Escape) {
+            confirmed = Some(false);
+        }
+        if confirmed.is_some() {
+            ui.close_current_popup();
+        }
+    });
+
+    if confirmed.is_some() || shown.is_none() {
+        if let Some((objective, source)) = state.pending_selection.take() {
+            if confirmed == Some(true) {
+                activate_objective(state, &objective, &source);
+            }
+        }
+    }
+}
+
+/// Copies an objective's coordinates, or its chat code if it has no position
+fn copy_objective(state: &mut AppState, objective: &Position) {
+    match (objective.pos, &objective.chat_code) {
+        (None, Some(chat_code)) => copy_chat_code(state, objective, chat_code),
+        (Some(pos), _) => {
+            let coords = state.config.format_output(pos);
+            copy_text(state, &coords, &format!("coordinates of {}", objective.name));
+        }
+        (None, None) => state.status = Some(format!("{} has nothing to copy", objective.name)),
+    }
+}
+
+/// Fills the right-click menu of an objective loaded from `source`; `hint_editable` is whether
+/// its row can show the hint editor
+fn objective_menu(
+    ui: &Ui,
+    state: &mut AppState,
+    objective: &Position,
+    source: &Path,
+    hint_editable: bool,
+) {
+    let chat_code = objective.chat_code.as_deref();
+    if ui.menu_item_config("Copy chat code").enabled(chat_code.is_some()).build() {
+        if let Some(chat_code) = chat_code {
+            copy_chat_code(state, objective, chat_code);
+        }
+    }
+    let map_url = objective.map.map(|map| state.config.map_url.replace("{map}", &map.to_string()));
+    if ui.menu_item_config("Open map in browser").enabled(map_url.is_some()).build() {
+        if let Some(url) = map_url {
+            state.status = Some(match open::that_detached(&url) {
+                Ok(()) => format!("Opened {}", url),
+                Err(e) => format!("Could not open {}: {}", url, e),
+            });
+        }
+    }
+    let line = objective.source.as_ref();
+    if ui.menu_item_config("Open in editor").enabled(line.is_some()).build() {
+        if let Some(line) = line {
+            open_in_editor(state, &line.path, line.line);
+        }
+    }
+    if hint_editable && ui.menu_item_config("Edit hint").enabled(line.is_some()).build() {
+        start_hint_edit(state, objective);
+    }
+    if ui.menu_item_config("Copy to another file...").enabled(line.is_some()).build() {
+        state.copy_to = Some(CopyTo {
+            objective: objective.clone(),
+            target: None,
+        });
+        state.copy_to_requested = true;
+    }
+    if ui.menu_item("Copy JSON") {
+        copy_text(state, &objective.to_json(), "JSON");
+    }
+    ui.separator();
+    if ui.menu_item("Compare as A") {
+        state.compare.pick(objective, false);
+    }
+    if ui.menu_item("Compare as B") {
+        state.compare.pick(objective, true);
+    }
+    if ui.menu_item("Add to queue") {
+        state.queue.push(objective, source);
+    }
+    if !state.selection.is_empty() {
+        ui.separator();
+        let count = state.selection.len();
+        if ui.menu_item(format!("Copy coordinates of {} selected", count)) {
+            copy_selection(state);
+        }
+        if ui.menu_item(format!("Add {} selected to queue", count)) {
+            for (objective, source) in state.selection.items() {
+                state.queue.push(objective, source);
+            }
+        }
+        if ui.menu_item("Clear selection") {
+            state.selection.clear();
+        }
+    }
+}
+
+/// Copies the coordinates of every multi-selected objective with a position, one per line
+fn copy_selection(state: &mut AppState) {
+    let lines: Vec<String> = state
+        .selection
+        .items()
+        .filter_map(|(objective, _)| objective.pos)
+        .map(|pos| state.config.format_output(pos))
+        .collect();
+    let what = format!("coordinates of {} objectives", lines.len());
+    copy_text(state, &(lines.join("\n")), &what);
+}
+
+/// Opens the inline hint editor for `objective` if its data file is known
+fn start_hint_edit(state: &mut AppState, objective: &Position) {
+    if let Some(source) = &objective.source {
+        state.hint_edit = Some(HintEdit {
+            source: source.clone(),
+            name: objective.name.clone(),
+            text: objective.hint.clone().unwrap_or_default(),
+            focus: true,
+            // Started from a menu, the row may already be drawn this frame
+            drawn: true,
+        });
+    }
+}
+
+/// Draws the inline hint editor; Enter saves the hint, Escape or clicking away cancels
+fn hint_editor(ui: &Ui, state: &mut AppState) {
+    let Some(edit) = state.hint_edit.as_mut() else {
+        return;
+    };
+    edit.drawn = true;
+    if std::mem::take(&mut edit.focus) {
+        ui.set_keyboard_focus_here();
+    }
+    let _width = ui.push_item_width(-1.0);
+    let save = ui.input_text("##hint", &mut edit.text).enter_returns_true(true).build();
+    if !save {
+        if ui.is_item_deactivated() {
+            state.hint_edit = None;
+        }
+        return;
+    }
+
+    let Some(edit) = state.hint_edit.take() else {
+        return;
+    };
+    let HintEdit { source, name, text, .. } = &edit;
+    state.status = Some(match edit::set_hint(&source.path, source.index, name, text.trim()) {
+        Ok(backup) => {
+            state.reload_requested = true;
+            format!("Saved the hint of {}, backup at {}", name, backup.display())
+        }
+        Err(e) => e,
+    });
+}
+
+/// Opens a data file at `line` with the configured editor and reports failures
+fn open_in_editor(state: &mut AppState, path: &Path, line: usize) {
+    state.status = Some(match edit::open_in_editor(&state.config.editor, path, line) {
+        Ok(()) => format!("Opened {} at line {}", path.display(), line),
+        Err(e) => e,
+    });
+}
+
+/// Draws a star toggle in `accent` when `active`, dimmed otherwise
+fn star_button(ui: &Ui, id: &str, active: bool, accent: [f32; 4]) -> bool {
+    let color = if active {
+        accent
+    } else {
+        ui.style_color(imgui::StyleColor::TextDisabled)
+    };
+    let _color = ui.push_style_color(imgui::StyleColor::Text, color);
+    ui.small_button(format!("*##{}", id))
+}
+
+/// Handles a single objective. `drag` is the objective's location and position when it
+/// can be reordered; returns the position dropped onto it and the position it was dropped at.
+fn handle_objective(
+    ui: &Ui,
+    state: &mut AppState,
+    objective: &Position,
+    source: &Path,
+    matched: Match,
+    drag: Option<(usize, usize)>,
+) -> Option<(usize, usize)> {
+    let _id = ui.push_id_ptr(objective);
+    let key = objective.key();
+    if star_button(ui, "favorite", state.favorites.contains(&key), state.config.accent()) {
+        state.favorites.toggle(key.clone());
+    }
+    ui.same_line();
+
+    if let Some(texture) = objective.icon.as_deref().and_then(|icon| state.icons.get(icon)) {
+        let size = ui.frame_height();
+        imgui::Image::new(texture, [size, size]).build(ui);
+        ui.same_line();
+    } else if state.config.tree_icons {
+        glyphs::item(ui, if objective.pos.is_some() { Glyph::Pin } else { Glyph::Point });
+        ui.same_line();
+    }
+
+    state.type_ahead.focus_if_match(ui, &objective.name);
+    reveal_if_match(ui, state, &key);
+    let name_color = matches!(matched, Match::Name | Match::Alias)
+        .then(|| ui.push_style_color(imgui::StyleColor::Text, state.config.accent()));
+    let other_map = ui.begin_disabled(!state.is_on_current_map(objective));
+    let highlight = state
+        .selection
+        .contains(&key)
+        .then(|| selection_highlight(ui, state.config.accent()));
+    let clicked = if state.config.full_row_select {
+        let _align = ui.push_style_var(imgui::StyleVar::SelectableTextAlign([0.0, 0.5]));
+        ui.selectable_config(&objective.name)
+            .flags(imgui::SelectableFlags::ALLOW_ITEM_OVERLAP)
+            .size([0.0, state.config.row_height])
+            .selected(highlight.is_some())
+            .build()
+    } else {
+        ui.button(&objective.name)
+    };
+    drop(highlight);
+    drop(name_color);
+    drop(other_map);
+    if preview_hovered(ui) {
+        objective_preview(ui, state, objective);
+    }
+    let mut dropped = None;
+    if let Some((location, index)) = drag {
+        if let Some(_tooltip) = ui
+            .drag_drop_source_config(REORDER_PAYLOAD)
+            .begin_payload((location, index))
+        {
+            ui.text(&objective.name);
+        }
+        if let Some(target) = ui.drag_drop_target() {
+            let flags = imgui::DragDropFlags::empty();
+            let payload = target.accept_payload::<(usize, usize), _>(REORDER_PAYLOAD, flags);
+            if let Some(Ok(payload)) = payload {
+                let (from_location, from) = payload.data;
+                if from_location == location && from != index {
+                    dropped = Some((from, index));
+                }
+            }
+            target.pop();
+        }
+    }
+    if ui.is_item_focused() && keys::pressed(ui, Action::CopyObjective) {
+        copy_objective(state, objective);
+    }
+    if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+        ui.open_popup("objective_menu");
+    }
+    if let Some(_popup) = ui.begin_popup("objective_menu") {
+        objective_menu(ui, state, objective, source, true);
+    }
+
+    if let Some(chat_code) = &objective.chat_code {
+        ui.same_line();
+        if ui.small_button("[&]") {
+            copy_chat_code(state, objective, chat_code);
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text(format!("Copy chat code {}", chat_code));
+        }
+    }
+    if state.config.last_selected.as_ref() == Some(&key) {
+        ui.same_line();
+        let color = ui.push_style_color(imgui::StyleColor::Text, state.config.accent());
+        ui.bullet();
+        color.pop();
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Last selected");
+        }
+    }
+    if state.config.show_usage_counts {
+        let count = state.usage.count(&key);
+        if count > 0 {
+            ui.same_line();
+            ui.text_disabled(format!("({})", count));
+        }
+    }
+    if clicked {
+        click_objective(ui, state, objective, source);
+    }
+
+    let editing = objective.source.as_ref().is_some_and(|source| {
+        state.hint_edit.as_ref().is_some_and(|edit| {
+            edit.source.path == source.path && edit.source.index == source.index
+        })
+    });
+    if editing {
+        ui.same_line();
+        hint_editor(ui, state);
+        return dropped;
+    }
+    match objective.hint.as_deref().filter(|hint| !hint.is_empty()) {
+        Some(hint) => {
+            ui.same_line();
+            if matched == Match::Hint {
+                ui.text_colored(state.config.accent(), hint);
+            } else {
+                ui.text(hint);
+            }
+            if ui.is_item_hovered() && ui.is_mouse_double_clicked(imgui::MouseButton::Left) {
+                start_hint_edit(state, objective);
+            }
+        }
+        None => {
+            if let Some(hint) = objective.pos.and_then(|pos| state.config.position_hint(pos)) {
+                ui.same_line();
+                ui.text_disabled(hint);
+            }
+        }
+    }
+    dropped
+}
+
+/// Lists every objective that passes the filters in the selected order, with its location
+/// below it; the rows are collected once until the tree, filters or search change, and only
+/// those scrolled into view are drawn
+fn handle_flat_view(ui: &Ui, state: &mut AppState, tree: &DataTree) {
+    let locations = tree.locations();
+    let key = state.flat_key();
+    let cached = state.flat_rows.take().filter(|cached| cached.key == key);
+    let flat = cached.unwrap_or_else(|| {
+        let mut rows: Vec<(&Position, (usize, usize, Match))> = locations
+            .iter()
+            .enumerate()
+            .filter(|(_, location)| state.is_location_visible(location))
+            .flat_map(|(index, location)| {
+                let objectives = location.data.objectives.iter().enumerate();
+                objectives.map(move |(objective_index, o)| (index, objective_index, o))
+            })
+            .filter(|(_, _, objective)| state.is_visible(objective))
+            .filter_map(|(index, objective_index, objective)| {
+                let matched = state.search.match_objective(objective)?;
+                Some((objective, (index, objective_index, matched)))
+            })
+            .collect();
+        sort_objectives(&mut rows, key.sort_mode, &state.usage);
+        if let Some(limit) = key.limit {
+            rows.truncate(limit);
+        }
+        let rows = rows.into_iter().map(|(_, row)| row).collect();
+        FlatRows { key, rows }
+    });
+    let row = |&(index, objective_index, _): &(usize, usize, Match)| {
+        let location = locations[index];
+        (location, &location.data.objectives[objective_index])
+    };
+
+    let clipper = imgui::ListClipper::new(flat.rows.len() as i32).begin(ui);
+    for index in clipper.iter() {
+        let flat_row = &flat.rows[index as usize];
+        let (location, objective) = row(flat_row);
+        let source = state.source_path(location);
+        handle_objective(ui, state, objective, source, flat_row.2, None);
+        ui.indent();
+        ui.text_disabled(&location.data.name);
+        ui.unindent();
+    }
+    if state.range_clicked.is_some() {
+        let view: Vec<_> = flat
+            .rows
+            .iter()
+            .map(|flat_row| {
+                let (location, objective) = row(flat_row);
+                (objective, state.source_path(location))
+            })
+            .collect();
+        resolve_range_click(state, &view);
+    }
+    state.flat_rows = Some(flat);
+}
+
+/// Orders objectives according to the sort mode
+fn sort_objectives<T>(objectives: &mut [(&Position, T)], sort_mode: SortMode, usage: &Usage) {
+    match sort_mode {
+        SortMode::FileOrder => {}
+        SortMode::Name => objectives.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name)),
+        SortMode::MapThenName => objectives.sort_by(|(a, _), (b, _)| {
+            // Objectives without a map go last
+            (a.map.is_none(), a.map, &a.name).cmp(&(b.map.is_none(), b.map, &b.name))
+        }),
+        SortMode::MostUsed => objectives.sort_by_cached_key(|(objective, _)| {
+            std::cmp::Reverse(usage.count(&objective.key()))
+        }),
+        SortMode::Priority => objectives.sort_by(|(a, _), (b, _)| a.cmp_priority(b)),
+    }
+}
+
+/// Fills the right-click menu of a location
+fn location_menu(ui: &Ui, state: &mut AppState, location: &Location) {
+    if ui.menu_item("Copy as Markdown table") {
+        let objectives = location
+            .data
+            .objectives
+            .iter()
+            .filter(|objective| !objective.hidden || state.show_hidden);
+        let table = export::markdown_table(objectives, &state.config);
+        copy_text(state, &table, &format!("{} as a Markdown table", location.data.name));
+    }
+    if ui.menu_item("Show parsed JSON") {
+        state.raw_json.show(location);
+    }
+}
+
+/// Orders two table view rows by the given column, coordinates in their displayed order
+fn compare_table_rows(
+    config: &Config,
+    column: usize,
+    (a_location, a): (&Location, &Position),
+    (b_location, b): (&Location, &Position),
+) -> std::cmp::Ordering {
+    // Missing maps and positions sort last, like in the map sort mode
+    let axis = |objective: &Position, index: usize| {
+        objective.pos.map_or((true, f32::NAN), |pos| (false, config.output_pos(pos)[index]))
+    };
+    match column {
+        0 => a_location.data.name.cmp(&b_location.data.name),
+        1 => a.name.cmp(&b.name),
+        2 => (a.map.is_none(), a.map).cmp(&(b.map.is_none(), b.map)),
+        _ => {
+            let (a_missing, a_axis) = axis(a, column - 3);
+            let (b_missing, b_axis) = axis(b, column - 3);
+            a_missing.cmp(&b_missing).then(a_axis.total_cmp(&b_axis))
+        }
+    }
+}
+
+/// Lists every objective that passes the filters in one sortable table
+fn handle_table_view(ui: &Ui, state: &mut AppState, tree: &DataTree) {
+
+    let mut rows: Vec<(&Location, &Position)> = tree
+        .locations()
+        .into_iter()
+        .flat_map(|location| location.data.objectives.iter().map(move |o| (location, o)))
+        .filter(|(location, objective)| {
+            state.is_location_visible(location)
+                && state.is_visible(objective)
+                && state.search.match_objective(objective).is_some()
+        })
+        .collect();
+
+    let flags = imgui::TableFlags::SORTABLE
+        | imgui::TableFlags::RESIZABLE
+        | imgui::TableFlags::ROW_BG
+        | imgui::TableFlags::BORDERS_INNER_V
+        | imgui::TableFlags::SCROLL_Y;
+    let Some(_table) = ui.begin_table_with_flags("objectives", 6, flags) else {
+        return;
+    };
+    ui.table_setup_scroll_freeze(0, 1);
+    for column in ["Location", "Name", "Map"] {
+        ui.table_setup_column(column);
+    }
+    // The IDs keep the sort column when `axis_order` changes
+    for (label, id) in state.config.axis_labels().iter().zip(["x", "y", "z"]) {
+        ui.table_setup_column(format!("{}###{}", label, id));
+    }
+    ui.table_headers_row();
+
+    if let Some(mut sort_specs) = ui.table_sort_specs_mut() {
+        if sort_specs.should_sort() {
+            state.table_sort = sort_specs.specs().iter().next().map(|spec| {
+                let direction = spec.sort_direction();
+                (spec.column_idx(), direction != Some(imgui::TableSortDirection::Descending))
+            });
+            sort_specs.set_sorted();
+        }
+    }
+    if let Some((column, ascending)) = state.table_sort {
+        rows.sort_by(|&a, &b| {
+            let ordering = compare_table_rows(&state.config, column, a, b);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+    }
+
+    if let Some(limit) = state.result_limit() {
+        rows.truncate(limit);
+    }
+    for (location, objective) in rows {
+        let _id = ui.push_id_ptr(objective);
+        let _other_map = ui.begin_disabled(!state.is_on_current_map(objective));
+        ui.table_next_row();
+        ui.table_next_column();
+        let clicked = ui
+            .selectable_config(&location.data.name)
+            .flags(imgui::SelectableFlags::SPAN_ALL_COLUMNS)
+            .build();
+        ui.table_next_column();
+        ui.text(&objective.name);
+        ui.table_next_column();
+        if let Some(map) = objective.map {
+            ui.text(map.to_string());
+        }
+        let axes = objective.pos.map(|pos| state.config.format_axes(pos));
+        for axis in axes.unwrap_or_default() {
+            ui.table_next_column();
+            ui.text(axis);
+        }
+
+        if clicked {
+            select_objective(state, objective, state.source_path(location));
+        }
+    }
+}
+
+/// Handles a location data and its objectives
+fn handle_location_data(ui: &Ui, state: &mut AppState, location: &Location, open: Option<bool>) {
+    let location_data = &location.data;
+    let source = state.source_path(location);
+    let _id = ui.push_id_ptr(location);
+    state.type_ahead.focus_if_match(ui, &location_data.name);
+    let tint = location_data
+        .color()
+        .map(|color| ui.push_style_color(imgui::StyleColor::Text, color));
+    let accordion = state.config.accordion && open.is_none() && !state.search.is_active();
+    let open = match &state.open_location {
+        Some(path) if accordion && *path != location.path => Some(false),
+        _ => open,
+    };
+    let glyph = state.config.tree_icons.then_some(Glyph::File);
+    let node = tree_node(ui, &location_data.name, glyph, open, state.search.is_active());
+    drop(tint);
+    let id = state.expansions.track(ui, node.is_some(), state.config.animate_tree);
+    if ui.is_item_clicked() || ui.is_item_toggled_open() {
+        if let Some(dir) = location.path.parent() {
+            state.set_current_dir(dir);
+        }
+    }
+    if accordion {
+        let is_current = state.open_location.as_ref() == Some(&location.path);
+        if node.is_some() && !is_current {
+            state.open_location = Some(location.path.clone());
+        } else if node.is_none() && is_current {
+            state.open_location = None;
+        }
+    }
+    if state.config.path_tooltips && ui.is_item_hovered() {
+        let path = std::path::absolute(&location.path).unwrap_or_else(|_| location.path.clone());
+        ui.tooltip_text(path.display().to_string());
+    }
+    if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+        ui.open_popup("location_menu");
+    }
+    if let Some(_popup) = ui.begin_popup("location_menu") {
+        location_menu(ui, state, location);
+    }
+    if state.is_changed(location) {
+        ui.same_line();
+        ui.text_colored(CHANGED_COLOR, "changed");
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Modified since the last run");
+        }
+    }
+
+    if let Some(reveal) = state.expansions.begin(ui, id, &location_data.name, node.is_some()) {
+        handle_location_objectives(ui, state, location, source);
+        state.expansions.end(ui, reveal);
+    } else {
+        state.skip_results([location]);
+    }
+}
+
+/// Draws the objectives of an open location
+fn handle_location_objectives(ui: &Ui, state: &mut AppState, location: &Location, source: &Path) {
+    let location_data = &location.data;
+    let mut objectives: Vec<_> = location_data
+        .objectives
+        .iter()
+        .filter(|objective| state.is_visible(objective))
+        .filter_map(|objective| Some((objective, state.search.match_objective(objective)?)))
+        .collect();
+    let sort_mode = state.config.sort_mode;
+    sort_objectives(&mut objectives, sort_mode, &state.usage);
+    if state.config.compact_objectives {
+        handle_compact_objectives(ui, state, &objectives, source);
+        return;
+    }
+
+    // Objectives can be dragged into a new order while the list matches the file
+    let own = own_objectives(location);
+    let reorderable = sort_mode == SortMode::FileOrder
+        && objectives.len() == location_data.objectives.len()
+        && state.reorder.as_ref().is_none_or(|reorder| reorder.path == location.path);
+    if let Some(reorder) = state.reorder.as_ref().filter(|_| reorderable) {
+        let original = objectives.clone();
+        for (slot, &index) in reorder.order.iter().enumerate() {
+            objectives[slot] = original[index];
+        }
+    }
+
+    let view: Vec<_> = objectives.iter().map(|&(objective, _)| (objective, source)).collect();
+    let mut current_map = None;
+    let mut dropped = None;
+    let columns = state.config.objective_columns;
+    let flags = imgui::TableFlags::SIZING_STRETCH_SAME;
+    let table = (columns > 1)
+        .then(|| ui.begin_table_with_flags("objective_columns", columns, flags))
+        .flatten();
+    for (index, (objective, matched)) in objectives.into_iter().enumerate() {
+        if state.results_exhausted() {
+            break;
+        }
+        state.results_shown += 1;
+        if sort_mode == SortMode::MapThenName && (index == 0 || current_map != objective.map) {
+            current_map = objective.map;
+            // Map headings get a row of their own, starting the next row of objectives
+            if table.is_some() {
+                ui.table_next_row();
+                ui.table_next_column();
+            }
+            match objective.map {
+                Some(map) => ui.text_disabled(format!("Map {}", map)),
+                None => ui.text_disabled("Unknown map"),
+            }
+            if table.is_some() {
+                ui.table_next_row();
+            }
+        }
+        if table.is_some() {
+            ui.table_next_column();
+        }
+        let location_id = location as *const Location as usize;
+        let drag = (reorderable && index < own).then_some((location_id, index));
+        dropped = handle_objective(ui, state, objective, source, matched, drag).or(dropped);
+    }
+    drop(table);
+    resolve_range_click(state, &view);
+
+    if let Some((from, to)) = dropped {
+        let reorder = state.reorder.get_or_insert_with(|| Reorder {
+            path: location.path.clone(),
+            order: (0..own).collect(),
+        });
+        let index = reorder.order.remove(from);
+        reorder.order.insert(to, index);
+    }
+    if reorderable && state.reorder.is_some() {
+        if ui.small_button("Save order...") {
+            state.save_reorder_requested = true;
+        }
+        ui.same_line();
+        if ui.small_button("Discard order") {
+            state.reorder = None;
+        }
+    }
+}
+
+/// Draws objectives as small buttons that flow onto the next line when the window is full,
+/// showing their details in tooltips
+fn handle_compact_objectives(
+    ui: &Ui,
+    state: &mut AppState,
+    objectives: &[(&Position, Match)],
+    source: &Path,
+) {
+    let style = ui.clone_style();
+    let visible_right = ui.window_pos()[0] + ui.window_content_region_max()[0];
+    for (index, &(objective, matched)) in objectives.iter().enumerate() {
+        if state.results_exhausted() {
+            break;
+        }
+        state.results_shown += 1;
+        let width = ui.calc_text_size(&objective.name)[0] + style.frame_padding[0] * 2.0;
+        if index > 0 && ui.item_rect_max()[0] + style.item_spacing[0] + width <= visible_right {
+            ui.same_line();
+        }
+
+        let _id = ui.push_id_ptr(objective);
+        state.type_ahead.focus_if_match(ui, &objective.name);
+        reveal_if_match(ui, state, &objective.key());
+        let name_color = matches!(matched, Match::Name | Match::Alias)
+            .then(|| ui.push_style_color(imgui::StyleColor::Text, state.config.accent()));
+        let other_map = ui.begin_disabled(!state.is_on_current_map(objective));
+        let highlight = state
+            .selection
+            .contains(&objective.key())
+            .then(|| selection_highlight(ui, state.config.accent()));
+        let clicked = ui.small_button(&objective.name);
+        drop(highlight);
+        drop(name_color);
+        drop(other_map);
+        if preview_hovered(ui) {
+            objective_preview(ui, state, objective);
+        }
+        if ui.is_item_clicked_with_button(imgui::MouseButton::Right) {
+            ui.open_popup("objective_menu");
+        }
+        if let Some(_popup) = ui.begin_popup("objective_menu") {
+            objective_menu(ui, state, objective, source, false);
+        }
+        if clicked {
+            click_objective(ui, state, objective, source);
+        }
+    }
+    let view: Vec<_> = objectives.iter().map(|&(objective, _)| (objective, source)).collect();
+    resolve_range_click(state, &view);
+}
+
+/// Scrolls to and focuses the next item if it is the objective being revealed
+fn reveal_if_match(ui: &Ui, state: &mut AppState, key: &ObjectiveKey) {
+    if state.reveal.as_ref() == Some(key) {
+        ui.set_scroll_here_y_with_ratio(0.5);
+        ui.set_keyboard_focus_here();
+        state.reveal = None;
+    }
+}
+
+/// Finds the listed objective nearest to the coordinates on the clipboard, opens the tree to
+/// it and reports how far away it is
+fn find_nearest_to_clipboard(state: &mut AppState, tree: &DataTree) {
+    let text = match state.clipboard.paste() {
+        Ok(text) => text,
+        Err(e) => {
+            state.status = Some(format!("Could not read the clipboard: {}", e));
+            return;
+        }
+    };
+    let Some(point) = data::parse_coordinates(&text) else {
+        state.status = Some("The clipboard does not hold x, y and z coordinates".to_string());
+        return;
+    };
+    let nearest = tree
+        .locations()
+        .into_iter()
+        .filter(|location| state.is_location_visible(location))
+        .flat_map(|location| &location.data.objectives)
+        .filter(|objective| {
+            state.is_visible(objective)
+                && state.is_on_current_map(objective)
+                && state.search.match_objective(objective).is_some()
+        })
+        .filter_map(|objective| Some((objective, objective.distance_to(point)?)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    state.status = Some(match nearest {
+        Some((objective, distance)) => {
+            state.reveal = Some(objective.key());
+            format!(
+                "Nearest to [{}]: {}, {:.1} away",
+                state.config.format_coords(point),
+                objective.name,
+                distance
+            )
+        }
+        None => "No listed objective has a position".to_string(),
+    });
+}
+
+/// Returns true if the objective button just drawn is hovered, even on another map, and
+/// not being dragged
+fn preview_hovered(ui: &Ui) -> bool {
+    ui.is_item_hovered_with_flags(imgui::ItemHoveredFlags::ALLOW_WHEN_DISABLED)
+        && !ui.is_mouse_dragging(imgui::MouseButton::Left)
+}
+
+/// Shows every detail of a hovered objective in a tooltip
+fn objective_preview(ui: &Ui, state: &AppState, objective: &Position) {
+    ui.tooltip(|| {
+        let _wrap = ui.push_text_wrap_pos_with_pos(ui.current_font_size() * 30.0);
+        ui.text(&objective.name);
+        ui.separator();
+        match objective.map {
+            Some(map) => ui.text(format!("Map: {}", map)),
+            None => ui.text_disabled("Unknown map"),
+        }
+        match objective.pos {
+            Some(pos) => ui.text(format!("Position: [{}]", state.config.format_coords(pos))),
+            None => ui.text_disabled("No position data"),
+        }
+        if !objective.path.is_empty() {
+            let count = objective.path.len();
+            ui.text(format!("Route: {} waypoint{}", count, if count == 1 { "" } else { "s" }));
+            route_plot(ui, objective, state.config.accent());
+        }
+        if let Some(hint) = objective.hint.as_deref().filter(|hint| !hint.is_empty()) {
+            ui.text_wrapped(format!("Hint: {}", hint));
+        }
+        if !objective.aliases.is_empty() {
+            ui.text_wrapped(format!("Aliases: {}", objective.aliases.join(", ")));
+        }
+        if let Some(chat_code) = &objective.chat_code {
+            ui.text(format!("Chat code: {}", chat_code));
+        }
+        ui.separator();
+        if let Some(source) = &objective.source {
+            ui.text_disabled(format!("{}, line {}", source.path.display(), source.line));
+        }
+        let count = state.usage.count(&objective.key());
+        ui.text_disabled(format!("Selected {} time{}", count, if count == 1 { "" } else { "s" }));
+    });
+}
+
+/// Draws an objective's route seen from above, x to the right and z down, as a line through
+/// its waypoints to its position, which is marked with a dot in `accent`
+fn route_plot(ui: &Ui, objective: &Position, accent: [f32; 4]) {
+    let points: Vec<[f32; 2]> = objective
+        .path
+        .iter()
+        .chain(&objective.pos)
+        .map(|pos| [pos[0], pos[2]])
+        .collect();
+    let size = ui.current_font_size() * 12.0;
+    let margin = 4.0;
+    let (mut min, mut max) = ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]);
+    for point in &points {
+        for axis in 0..2 {
+            min[axis] = min[axis].min(point[axis]);
+            max[axis] = max[axis].max(point[axis]);
+        }
+    }
+    // One scale for both axes keeps the route's shape, and a route along a line stays centered
+    let extent = (max[0] - min[0]).max(max[1] - min[1]).max(f32::EPSILON);
+    let scale = (size - margin * 2.0) / extent;
+    let origin = ui.cursor_screen_pos();
+    let at = |point: &[f32; 2]| {
+        [0, 1].map(|axis| {
+            let offset = (size - (max[axis] - min[axis]) * scale) / 2.0;
+            origin[axis] + offset + (point[axis] - min[axis]) * scale
+        })
+    };
+    ui.dummy([size, size]);
+
+    let draw_list = ui.get_window_draw_list();
+    let border = ui.style_color(imgui::StyleColor::Border);
+    let [x, y] = origin;
+    draw_list.add_rect(origin, [x + size, y + size], border).build();
+    let line = ui.style_color(imgui::StyleColor::Text);
+    let screen: Vec<[f32; 2]> = points.iter().map(at).collect();
+    draw_list.add_polyline(screen.clone(), line).thickness(1.5).build();
+    for point in &screen {
+        draw_list.add_circle(*point, 2.0, line).filled(true).build();
+    }
+    if objective.pos.is_some() {
+        if let Some(destination) = screen.last() {
+            draw_list.add_circle(*destination, 4.0, accent).filled(true).build();
+        }
+    }
+}
+
+/// Pushes a translucent `accent` as button and header color, marking multi-selected objectives
+fn selection_highlight(ui: &Ui, accent: [f32; 4]) -> [imgui::ColorStackToken<'_>; 2] {
+    let [r, g, b, _] = accent;
+    [
+        ui.push_style_color(imgui::StyleColor::Button, [r, g, b, 0.45]),
+        ui.push_style_color(imgui::StyleColor::Header, [r, g, b, 0.45]),
+    ]
+}
 
-/// Represents a position or objective in the game world
-#[derive(Deserialize)]
-struct Position {
-    name: String,
-    #[serde(default)]
-    hint: Option<String>,
-    #[serde(skip_serializing)]
-    #[allow(dead_code)]
-    map: Option<i32>,
-    #[serde(default)]
-    pos: Option<[f32; 3]>
+/// Handles a click on an objective: Ctrl toggles it in the multi-selection, Shift selects a
+/// range up to it once its list is drawn, and a plain click runs the selection action, or the
+/// second of two quick clicks when activating on double click
+fn click_objective(ui: &Ui, state: &mut AppState, objective: &Position, source: &Path) {
+    let (ctrl, shift) = (ui.io().key_ctrl, ui.io().key_shift);
+    if shift {
+        state.range_clicked = Some(objective.key());
+    } else if ctrl {
+        state.selection.toggle(objective, source);
+    } else {
+        state.selection.click(objective);
+        // Enter and Space press the button without releasing the mouse
+        let by_mouse = ui.is_mouse_released(imgui::MouseButton::Left);
+        if state.config.activation == Activation::Single || !by_mouse {
+            select_objective(state, objective, source);
+            return;
+        }
+        let key = objective.key();
+        let double_click = Duration::from_secs_f32(ui.io().mouse_double_click_time);
+        let second = state.last_click.take().is_some_and(|(last, time)| {
+            last == key && time.elapsed() <= double_click
+        });
+        if second {
+            select_objective(state, objective, source);
+        } else {
+            state.last_click = Some((key, Instant::now()));
+            state.status = Some(format!("Double-click {} to teleport", objective.name));
+        }
+    }
 }
 
-/// Represents a collection of positions/objectives
-#[derive(Deserialize)]
-struct LocationData {
-    name: String,
-    objectives: Vec<Position>
+/// Selects the range for an objective Shift-clicked in `view`, the list as drawn
+fn resolve_range_click(state: &mut AppState, view: &[(&Position, &Path)]) {
+    let Some(key) = state.range_clicked.take() else {
+        return;
+    };
+    if let Some(clicked) = view.iter().position(|(objective, _)| objective.key() == key) {
+        state.selection.select_range(view, clicked);
+    }
 }
 
-/// Handles a single objective
-fn handle_objective(ui: &Ui, objective: Position) {
-    if ui.button(&objective.name) {
-        match objective.pos {
-            Some(pos) => println!(
-                "Selected position: {} at [{:.2}, {:.2}, {:.2}]", 
-                objective.name, pos[0], pos[1], pos[2]
-            ),
-            None => println!("Selected: {} (no position data)", objective.name),
+/// Returns how many objectives come from the location's own file rather than includes,
+/// which are appended after them
+fn own_objectives(location: &Location) -> usize {
+    location
+        .data
+        .objectives
+        .iter()
+        .take_while(|objective| {
+            objective.source.as_ref().is_some_and(|source| source.path == location.path)
+        })
+        .count()
+}
+
+/// Draws a tree node with `glyph` before its label, first forcing it open or closed if `open`
+/// is set; while `searching`, only nodes leading to matches are drawn, and they start out open
+fn tree_node<'ui>(
+    ui: &'ui Ui,
+    label: &str,
+    glyph: Option<Glyph>,
+    open: Option<bool>,
+    searching: bool,
+) -> Option<imgui::TreeNodeToken<'ui>> {
+    // The ID comes from the label alone so toggling icons keeps nodes open
+    let padding = glyph.map(|_| glyphs::label_padding(ui)).unwrap_or_default();
+    let mut node = ui.tree_node_config(format!("{}{}###{}", padding, label, label));
+    if let Some(open) = open {
+        node = node.opened(open, imgui::Condition::Always);
+    } else if searching {
+        node = node.opened(true, imgui::Condition::FirstUseEver);
+    }
+    let token = node.push();
+    if let Some(glyph) = glyph {
+        glyphs::draw_on_tree_node(ui, glyph);
+    }
+    token
+}
+
+/// Says how many search results are hidden by `max_search_results`, with a button to list all
+fn result_limit_note(ui: &Ui, state: &mut AppState, tree: &DataTree) {
+    if !state.search.is_active() {
+        state.show_all_results = false;
+        return;
+    }
+    let Some(limit) = state.result_limit() else {
+        return;
+    };
+    let total = tree
+        .locations()
+        .into_iter()
+        .filter(|location| state.is_location_visible(location))
+        .flat_map(|location| &location.data.objectives)
+        .filter(|objective| {
+            state.is_visible(objective) && state.search.match_objective(objective).is_some()
+        })
+        .count();
+    if total > limit {
+        ui.text_disabled(format!("Showing {} of {} results", limit, total));
+        ui.same_line();
+        if ui.small_button("Show all") {
+            state.show_all_results = true;
         }
     }
-    
-    if let Some(hint) = &objective.hint {
-        if !hint.is_empty() {
-            ui.same_line();
-            ui.text(hint);
+}
+
+/// Recursively handles the loaded nodes and creates the UI tree structure;
+/// `expand` opens every node in `nodes` this frame
+fn handle_nodes(ui: &Ui, state: &mut AppState, nodes: &[Node], expand: bool) {
+    for node in nodes {
+        let revealed = state.reveal.as_ref().is_some_and(|key| node.contains(key));
+        let open = (expand || revealed).then_some(true);
+        if state.results_exhausted() {
+            return;
+        }
+        if state.is_filtering()
+            && !state.search.node_has_match(node, &|location, objective| {
+                state.is_location_visible(location) && state.is_visible(objective)
+            })
+        {
+            continue;
+        }
+
+        match node {
+            Node::Directory {
+                name,
+                path,
+                meta,
+                children,
+            } => {
+                state.type_ahead.focus_if_match(ui, name);
+                let glyph = state.config.tree_icons.then_some(Glyph::Folder);
+                let searching = state.search.is_active();
+                let tint = meta
+                    .color()
+                    .map(|color| ui.push_style_color(imgui::StyleColor::Text, color));
+                let node = tree_node(ui, name, glyph, open, searching);
+                drop(tint);
+                let id = state.expansions.track(ui, node.is_some(), state.config.animate_tree);
+                if ui.is_item_clicked() || ui.is_item_toggled_open() {
+                    state.set_current_dir(path);
+                }
+                if let Some(reveal) = state.expansions.begin(ui, id, name, node.is_some()) {
+                    handle_nodes(ui, state, children, false);
+                    state.expansions.end(ui, reveal);
+                } else {
+                    state.skip_results(children.iter().flat_map(Node::locations));
+                }
+            }
+            Node::Location(location) => {
+                // A file loaded on expansion stays open under its parsed name
+                let loaded = state.lazily_opened.iter().position(|path| *path == location.path);
+                let open = open.or(loaded.map(|index| {
+                    state.lazily_opened.remove(index);
+                    true
+                }));
+                handle_location_data(ui, state, location, open)
+            }
+            Node::Pending { name, path } => handle_pending(ui, state, name, path, open),
         }
     }
 }
 
-/// Handles a location data and its objectives
-fn handle_location_data(ui: &Ui, location_data: LocationData) {
-    if let Some(_node_token) = ui.tree_node(&location_data.name) {
-        for objective in location_data.objectives {
-            handle_objective(ui, objective);
+/// Draws a file that is not parsed yet, asking for it to be loaded once it is opened
+fn handle_pending(ui: &Ui, state: &mut AppState, name: &str, path: &Path, open: Option<bool>) {
+    state.type_ahead.focus_if_match(ui, name);
+    let glyph = state.config.tree_icons.then_some(Glyph::File);
+    if let Some(_node) = tree_node(ui, name, glyph, open, state.search.is_active()) {
+        let spinner = ["|", "/", "-", "\\"][(ui.time() * 8.0) as usize % 4];
+        ui.text_disabled(format!("{} Loading...", spinner));
+        if !state.pending_loads.iter().any(|pending| pending == path) {
+            state.pending_loads.push(path.to_path_buf());
         }
     }
 }
 
-/// Recursively handles directory contents and creates the UI tree structure
-fn handle_directory(ui: &Ui, path: PathBuf) {
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.filter_map(Result::ok) {
-            let path = entry.path();
-            let name = path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown");
+/// Lists the files that failed to load, with fixes for invalid positions; `errors_key`
+/// opens or closes it and moves the keyboard focus to it.
+/// Returns true if a file was rewritten and the tree needs reloading.
+fn errors_panel(ui: &Ui, state: &mut AppState, errors: &[LoadError]) -> bool {
+    let toggled = !ui.io().want_text_input && keys::chord_pressed(ui, &state.config.errors_key);
+    if errors.len() > state.errors_seen {
+        state.errors_flash = Some(Instant::now());
+    }
+    state.errors_seen = errors.len();
+    if errors.is_empty() {
+        state.errors_open = false;
+        state.errors_flash = None;
+        if toggled {
+            state.status = Some("No errors".to_string());
+        }
+        return false;
+    }
+
+    let condition = if toggled {
+        imgui::Condition::Always
+    } else {
+        imgui::Condition::Never
+    };
+    if toggled && !state.errors_open {
+        ui.set_keyboard_focus_here();
+    }
+    let header = ui
+        .tree_node_config(format!("Errors ({})###errors", errors.len()))
+        .flags(imgui::TreeNodeFlags::FRAMED | imgui::TreeNodeFlags::NO_TREE_PUSH_ON_OPEN)
+        .opened(!state.errors_open, condition)
+        .push();
+    state.errors_open = header.is_some();
+    if toggled && state.errors_open {
+        ui.set_scroll_here_y_with_ratio(0.0);
+    }
+    let hint = !state.errors_open && keys::parse_chord(&state.config.errors_key).is_some();
+    match state.errors_flash.map(|start| start.elapsed().as_secs_f32()) {
+        Some(secs) if secs >= ERRORS_FLASH.as_secs_f32() => state.errors_flash = None,
+        Some(secs) if hint => {
+            let mut color = state.config.accent();
+            color[3] = 0.6 + 0.4 * (secs * std::f32::consts::TAU).cos();
+            ui.same_line();
+            ui.text_colored(color, format!("Press {} to show", state.config.errors_key));
+        }
+        _ => {}
+    }
+    if !state.errors_open {
+        return false;
+    }
 
-            if path.is_dir() {
-                if let Some(_token) = ui.tree_node(name) {
-                    handle_directory(ui, path);
+    let mut fixed = false;
+    for (error_index, error) in errors.iter().enumerate() {
+        let _id = ui.push_id_usize(error_index);
+        ui.text_wrapped(format!("{}: {}", error.path.display(), error.message));
+        if let Some(line) = error.line {
+            if ui.small_button("Open at line") {
+                open_in_editor(state, &error.path, line);
+            }
+        }
+        for issue in &error.pos_issues {
+            let _id = ui.push_id_usize(issue.index);
+            ui.bullet_text(format!("{}: invalid pos {}", issue.name, issue.pos));
+            if let Some(line) = issue.line {
+                ui.same_line();
+                if ui.small_button("Open") {
+                    open_in_editor(state, &error.path, line);
                 }
-            } else if path.extension().map_or(false, |ext| ext == "json") {
-                if let Ok(json_content) = fs::read_to_string(&path) {
-                    match serde_json::from_str::<LocationData>(&json_content) {
-                        Ok(location_data) => handle_location_data(ui, location_data),
-                        Err(e) => eprintln!("Error parsing {}: {}", name, e),
-                    }
+            }
+            let fixes = [("Pad/truncate", PosFix::PadOrTruncate), ("Remove pos", PosFix::Remove)];
+            for (label, fix) in fixes {
+                ui.same_line();
+                if ui.small_button(label) {
+                    state.fix_status = Some(match edit::fix_pos(&error.path, issue, fix) {
+                        Ok(backup) => {
+                            fixed = true;
+                            format!("Fixed {}, backup at {}", issue.name, backup.display())
+                        }
+                        Err(e) => e,
+                    });
                 }
             }
         }
     }
+    if let Some(status) = &state.fix_status {
+        ui.text_wrapped(status);
+    }
+    fixed
+}
+
+/// Answers the calls the HTTP server received since the last frame
+#[cfg(feature = "http-server")]
+fn handle_api_requests(state: &mut AppState, tree: &DataTree) {
+    let Some(api) = state.api.take() else {
+        return;
+    };
+    for request in api.try_iter() {
+        let reply = match request.call {
+            http::ApiCall::Locations => (200, http::locations_body(tree, &state.data_dir)),
+            http::ApiCall::Select { name } => match select_by_name(state, tree, &name) {
+                Ok(()) => (200, serde_json::json!({ "selected": name }).to_string()),
+                Err(e) => (404, http::error_body(&e)),
+            },
+        };
+        // The client may have hung up, which is not an error on our side
+        let _ = request.reply.send(reply);
+    }
+    state.api = Some(api);
+}
+
+/// Lets the user pick a data directory and switches the tree to it
+fn open_directory(state: &mut AppState) {
+    let Some(dir) = rfd::FileDialog::new().set_directory(&state.data_dir).pick_folder() else {
+        return;
+    };
+    switch_directory(state, dir);
+}
+
+/// Loads `dir` and records it as the most recent directory
+fn switch_directory(state: &mut AppState, dir: PathBuf) {
+    state.status = Some(format!("Opened {}", dir.display()));
+    state.data_dir = dir.clone();
+    state.config.add_recent_dir(dir);
+    state.config.save();
+    state.reload_requested = true;
+}
+
+/// Fills the File > Open recent menu
+fn recent_menu(ui: &Ui, state: &mut AppState) {
+    let mut opened = None;
+    for dir in &state.config.recent_dirs {
+        if ui.menu_item(dir.display().to_string()) {
+            opened = Some(dir.clone());
+        }
+    }
+    if let Some(dir) = opened {
+        if dir.is_dir() {
+            switch_directory(state, dir);
+        } else {
+            state.status = Some(format!("{} no longer exists", dir.display()));
+            state.config.prune_recent_dirs();
+            state.config.save();
+        }
+    }
+
+    ui.separator();
+    if ui.menu_item("Clear recent") {
+        state.config.recent_dirs.clear();
+        state.config.save();
+    }
+}
+
+/// Saves the favorites and usage counts to a file the user picks
+fn export_profile(state: &mut AppState) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("JSON", &["json"])
+        .set_file_name("teleport-profile.json")
+        .save_file()
+    else {
+        return;
+    };
+    state.status = Some(match profile::export(&path, &state.favorites, &state.usage) {
+        Ok(()) => format!("Exported favorites and usage to {}", path.display()),
+        Err(e) => e,
+    });
+}
+
+/// Loads favorites and usage counts from a file the user picks
+fn import_profile(state: &mut AppState, mode: profile::ImportMode) {
+    let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+        return;
+    };
+    let result = profile::import(&path, &mut state.favorites, &mut state.usage, mode);
+    state.status = Some(match result {
+        Ok(()) => format!("Imported favorites and usage from {}", path.display()),
+        Err(e) => e,
+    });
+}
+
+/// Writes the example file into the data directory
+fn create_example(state: &mut AppState) {
+    state.status = Some(match data::create_example_file(&state.data_dir) {
+        Ok(path) => {
+            state.reload_requested = true;
+            format!("Created {}", path.display())
+        }
+        Err(e) => e,
+    });
+}
+
+/// Converts a CSV file the user picks into a location file in the data directory
+fn import_csv(state: &mut AppState) {
+    let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() else {
+        return;
+    };
+    state.status = Some(match csv_import::import_csv(&path, &state.data_dir) {
+        Ok(report) => {
+            state.reload_requested = true;
+            for (line, e) in &report.skipped {
+                eprintln!("Error parsing {} line {}: {}", path.display(), line, e);
+            }
+            let mut status = format!(
+                "Imported {} objectives into {}",
+                report.imported,
+                report.path.display()
+            );
+            if !report.skipped.is_empty() {
+                let lines: Vec<String> =
+                    report.skipped.iter().map(|(line, _)| line.to_string()).collect();
+                status += &format!("; skipped malformed lines {}", lines.join(", "));
+            }
+            status
+        }
+        Err(e) => e,
+    });
+}
+
+/// Shows FPS and frame time in the top right corner, toggled with F3
+fn fps_overlay(ui: &Ui, state: &mut AppState) {
+    if keys::pressed(ui, Action::ToggleFps) {
+        state.config.show_fps = !state.config.show_fps;
+        state.config.save();
+    }
+    if !state.config.show_fps {
+        return;
+    }
+
+    const MARGIN: f32 = 10.0;
+    let [width, _] = ui.io().display_size;
+    let frame_time = ui.io().delta_time;
+    ui.window("fps_overlay")
+        .position([width - MARGIN, MARGIN], imgui::Condition::Always)
+        .position_pivot([1.0, 0.0])
+        .bg_alpha(0.35)
+        .flags(
+            imgui::WindowFlags::NO_DECORATION
+                | imgui::WindowFlags::NO_INPUTS
+                | imgui::WindowFlags::NO_NAV
+                | imgui::WindowFlags::ALWAYS_AUTO_RESIZE
+                | imgui::WindowFlags::NO_SAVED_SETTINGS
+                | imgui::WindowFlags::NO_FOCUS_ON_APPEARING,
+        )
+        .build(|| {
+            ui.text(format!("{:.0} FPS", ui.io().framerate));
+            ui.text(format!("{:.2} ms", frame_time * 1000.0));
+        });
+}
+
+/// Shows the keyboard shortcut overlay while it is open
+fn keybind_cheatsheet(ui: &Ui, state: &mut AppState) {
+    if state.cheatsheet_open {
+        // Any key or click dismisses the overlay, including the ones that opened it
+        if keys::any_pressed(ui) {
+            state.cheatsheet_open = false;
+            return;
+        }
+    } else if !ui.io().want_text_input && keys::pressed(ui, Action::ToggleCheatsheet) {
+        state.cheatsheet_open = true;
+    }
+    if !state.cheatsheet_open {
+        return;
+    }
+
+    let [width, height] = ui.io().display_size;
+    ui.window("Keyboard shortcuts")
+        .position([width * 0.5, height * 0.5], imgui::Condition::Always)
+        .position_pivot([0.5, 0.5])
+        .flags(
+            imgui::WindowFlags::ALWAYS_AUTO_RESIZE
+                | imgui::WindowFlags::NO_MOVE
+                | imgui::WindowFlags::NO_COLLAPSE
+                | imgui::WindowFlags::NO_SAVED_SETTINGS,
+        )
+        .build(|| {
+            // Keys set in the config are listed after the fixed ones, when they are valid
+            let configured = [
+                (state.config.theme_key.as_str(), "Switch to the next theme"),
+                (state.config.errors_key.as_str(), "Show or hide the errors panel"),
+            ];
+            let rows: Vec<(&str, &str)> = keys::KEYBINDINGS
+                .iter()
+                .map(|binding| (binding.keys, binding.description))
+                .chain(configured.into_iter().filter(|(key, _)| keys::parse_chord(key).is_some()))
+                .collect();
+            let keys_width = rows
+                .iter()
+                .map(|(key, _)| ui.calc_text_size(key)[0])
+                .fold(0.0, f32::max);
+            let spacing = ui.clone_style().item_spacing[0];
+            let description_x = ui.cursor_pos()[0] + keys_width + 2.0 * spacing;
+            for (key, description) in rows {
+                ui.text(key);
+                ui.same_line_with_pos(description_x);
+                ui.text(description);
+            }
+            ui.separator();
+            ui.text_disabled("Press any key or click to close");
+        });
+}
+
+/// Applies edits made to `config.toml` while the app runs
+fn reload_config(state: &mut AppState) {
+    if !state.config_watcher.changed() {
+        return;
+    }
+    match Config::read() {
+        Ok(config) if config != state.config => {
+            let restart_only = state.config.restart_only_changes(&config);
+            state.status = Some(if restart_only.is_empty() {
+                "Reloaded config.toml".to_string()
+            } else {
+                format!(
+                    "Reloaded config.toml; {} apply on the next launch",
+                    restart_only.join(", ")
+                )
+            });
+            if config.data_dir != state.config.data_dir {
+                state.data_dir = config.data_dir.clone();
+                state.reload_requested = true;
+            }
+            state.reload_requested |= config.needs_reload(&state.config);
+            state.config = config;
+        }
+        Ok(_) => {}
+        Err(e) => state.status = Some(e),
+    }
+}
+
+/// Draws the search box with a dropdown of recent queries; a query is remembered when
+/// Enter is pressed or the box loses focus after an edit
+fn search_box(ui: &Ui, state: &mut AppState) {
+    if !state.config.search_history.is_empty() {
+        if ui.arrow_button("##search_history", imgui::Direction::Down) {
+            ui.open_popup("search_history");
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Recent searches");
+        }
+        ui.same_line_with_spacing(0.0, ui.clone_style().item_inner_spacing[0]);
+    }
+    if let Some(_popup) = ui.begin_popup("search_history") {
+        let mut picked = None;
+        for query in &state.config.search_history {
+            if ui.selectable(query) {
+                picked = Some(query.clone());
+            }
+        }
+        if let Some(query) = picked {
+            state.search.query = query;
+        }
+    }
+
+    let had_query = !state.search.query.is_empty();
+    ui.input_text("Search", &mut state.search.query).build();
+    // imgui's Escape reverts the edit and leaves the box; with a query it clears it instead
+    // and keeps the focus, so a second Escape leaves the box
+    if ui.is_item_deactivated() && ui.is_key_pressed(imgui::Key::Escape) && had_query {
+        state.search.query.clear();
+        ui.set_keyboard_focus_here_with_offset(imgui::FocusedWidget::Previous);
+    }
+    let entered = ui.is_item_focused() && ui.is_key_pressed(imgui::Key::Enter);
+    let query = state.search.query.trim().to_string();
+    if (entered || ui.is_item_deactivated_after_edit()) && !query.is_empty() {
+        state.config.add_search(&query);
+        state.config.save();
+    }
+
+    let Some(dir) = state.search.scope.clone().or_else(|| state.current_dir.clone()) else {
+        return;
+    };
+    ui.same_line();
+    let name = dir.file_name().unwrap_or(dir.as_os_str()).to_string_lossy();
+    let mut scoped = state.search.scope.is_some();
+    if ui.checkbox(format!("Only in {}###search_scope", name), &mut scoped) {
+        state.search.scope = scoped.then_some(dir.clone());
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text(format!("Search only {} and its subdirectories", dir.display()));
+    }
+}
+
+/// Draws the min/max inputs of the coordinate range filter, which bound the coordinates
+/// named by `labels` as they are displayed
+fn range_filter(ui: &Ui, range: &mut RangeFilter, labels: &[String; 3]) {
+    let label = if range.is_active() {
+        "Coordinate range (active)###range"
+    } else {
+        "Coordinate range###range"
+    };
+    let Some(_node) = ui.tree_node(label) else {
+        return;
+    };
+
+    let width = ui.calc_text_size("-000000.00")[0] + 2.0 * ui.clone_style().frame_padding[0];
+    let mut changed = false;
+    for ((index, axis), inputs) in labels.iter().enumerate().zip(&mut range.inputs) {
+        let _id = ui.push_id_usize(index);
+        let [min, max] = inputs;
+        ui.set_next_item_width(width);
+        changed |= ui.input_text("##min", min).hint("min").build();
+        ui.same_line();
+        ui.set_next_item_width(width);
+        changed |= ui.input_text("##max", max).hint("max").build();
+        ui.same_line();
+        ui.text(axis);
+    }
+    if changed {
+        range.update();
+    }
+    if ui.button("Clear range") {
+        range.clear();
+    }
 }
 
 /// Creates the teleport window with the directory tree
-fn teleport_window(ui: &Ui) {
+fn teleport_window(ui: &Ui, state: &mut AppState, tree: &mut DataTree) {
+    reload_config(state);
+    #[cfg(feature = "http-server")]
+    handle_api_requests(state, tree);
+    // Files opened last frame are parsed now, once the frame showing their spinner is on screen
+    if !state.pending_loads.is_empty() {
+        for path in std::mem::take(&mut state.pending_loads) {
+            if tree.load_pending(&path) {
+                state.lazily_opened.push(path);
+            }
+            state.tree_revision += 1;
+        }
+        check_positions(tree, &state.config, &state.data_dir);
+    }
+
+    let density = state.config.density;
+    let _padding = ui.push_style_var(imgui::StyleVar::FramePadding(density.frame_padding()));
+    let _spacing = ui.push_style_var(imgui::StyleVar::ItemSpacing(density.item_spacing()));
+    let _rounding = ui.push_style_var(imgui::StyleVar::FrameRounding(state.config.frame_rounding));
+    let _border = ui.push_style_var(imgui::StyleVar::FrameBorderSize(state.config.frame_border));
+
+    let layout = if std::mem::take(&mut state.reset_layout) {
+        imgui::Condition::Always
+    } else {
+        imgui::Condition::FirstUseEver
+    };
     ui.window("Teleport")
-        .size([400.0, 600.0], imgui::Condition::FirstUseEver)
+        .position([60.0, 60.0], layout)
+        .size([400.0, 600.0], layout)
+        .collapsed(false, layout)
+        .menu_bar(true)
         .build(|| {
-            handle_directory(ui, PathBuf::from("data"));
+            state.type_ahead.update(ui);
+            if keys::pressed(ui, Action::OpenDirectory) {
+                open_directory(state);
+            }
+            let theme_key = &state.config.theme_key;
+            if !ui.io().want_text_input && keys::chord_pressed(ui, theme_key) {
+                state.config.theme = state.config.theme.next();
+                state.config.save();
+                state.status = Some(format!("Theme: {}", state.config.theme.label()));
+            }
+
+            ui.menu_bar(|| {
+                ui.menu("File", || {
+                    if ui.menu_item_config("Open directory...").shortcut("Ctrl+O").build() {
+                        open_directory(state);
+                    }
+                    let has_recent = !state.config.recent_dirs.is_empty();
+                    if let Some(_menu) = ui.begin_menu_with_enabled("Open recent", has_recent) {
+                        recent_menu(ui, state);
+                    }
+                    if ui.menu_item("Reload") {
+                        state.reload_requested = true;
+                    }
+                    if ui.menu_item("Create example file") {
+                        create_example(state);
+                    }
+                    if ui.menu_item("Import objectives from CSV...") {
+                        import_csv(state);
+                    }
+                    ui.separator();
+                    if ui.menu_item("Export favorites and usage...") {
+                        export_profile(state);
+                    }
+                    ui.menu("Import favorites and usage", || {
+                        if ui.menu_item("Merge with current...") {
+                            import_profile(state, profile::ImportMode::Merge);
+                        }
+                        if ui.menu_item("Replace current...") {
+                            import_profile(state, profile::ImportMode::Replace);
+                        }
+                    });
+                    ui.separator();
+                    if ui.menu_item("Settings...") {
+                        state.settings.show(&state.config);
+                    }
+                });
+                ui.menu("Tools", || {
+                    if ui.menu_item("Compare objectives") {
+                        state.compare.open = true;
+                    }
+                    if ui.menu_item("Teleport queue") {
+                        state.queue.open = true;
+                    }
+                    if ui.menu_item("Find nearest to copied position") {
+                        find_nearest_to_clipboard(state, tree);
+                    }
+                    if ui.menu_item("Missing metadata report") {
+                        state.report.show(tree, &state.data_dir);
+                    }
+                    if ui.menu_item("Compare directories") {
+                        state.diff.show(&state.data_dir);
+                    }
+                    #[cfg(debug_assertions)]
+                    ui.menu("Test selection", || test_selection_menu(ui, state, tree));
+                    #[cfg(feature = "mumble-link")]
+                    if ui.menu_item("Position picker") {
+                        state.picker.open = true;
+                    }
+                });
+                ui.menu("Help", || {
+                    if ui.menu_item_config("Keyboard shortcuts").shortcut("F1").build() {
+                        state.cheatsheet_open = true;
+                    }
+                });
+            });
+
+            ui.checkbox("Show hidden objectives", &mut state.show_hidden);
+            ui.same_line();
+            if ui.button(density.toggle_label()) {
+                state.config.density = density.toggled();
+                state.config.save();
+            }
+            for mode in ViewMode::ALL {
+                ui.same_line();
+                if ui.radio_button_bool(mode.label(), state.config.view_mode == mode) {
+                    state.config.view_mode = mode;
+                    state.config.save();
+                }
+            }
+
+            #[cfg(feature = "mumble-link")]
+            if state.follow_game_map {
+                if let Ok(player) = mumble::read_player_state() {
+                    state.current_map = player.map;
+                }
+            }
+            ui.set_next_item_width(100.0);
+            ui.input_int("Current map", &mut state.current_map).build();
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Objectives on other maps are grayed out, 0 disables this");
+            }
+            ui.same_line();
+            ui.checkbox("Hide other maps", &mut state.hide_other_maps);
+            #[cfg(feature = "mumble-link")]
+            {
+                ui.same_line();
+                ui.checkbox("Follow game", &mut state.follow_game_map);
+            }
+
+            let mut sort_index = SortMode::ALL
+                .iter()
+                .position(|&mode| mode == state.config.sort_mode)
+                .unwrap_or(0);
+            if ui.combo("Sort", &mut sort_index, &SortMode::ALL, |mode| mode.label().into()) {
+                state.config.sort_mode = SortMode::ALL[sort_index];
+                state.config.save();
+            }
+
+            if !state.favorites.is_empty() {
+                if star_button(ui, "favorites_only", state.favorites_only, state.config.accent()) {
+                    state.favorites_only = !state.favorites_only;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Show only favorites");
+                }
+                ui.same_line();
+            }
+            search_box(ui, state);
+            ui.same_line();
+            ui.checkbox("Search hints too", &mut state.search.include_hints);
+            if state.previous_launch.is_some() {
+                ui.same_line();
+                ui.checkbox("Changed since last run", &mut state.changed_only);
+            }
+            range_filter(ui, &mut state.range, &state.config.axis_labels());
+            if let Some(status) = &state.status {
+                ui.text_disabled(status);
+            }
+            ui.separator();
+
+            if tree.summary.files > 0 {
+                ui.text_disabled(tree.summary.to_string());
+            }
+            if errors_panel(ui, state, &tree.errors) {
+                state.reload_requested = true;
+            }
+            if tree.nodes.is_empty() {
+                ui.text_wrapped(format!("No location files in {}", state.data_dir.display()));
+                if ui.button("Open directory...") {
+                    open_directory(state);
+                }
+                ui.same_line();
+                if ui.button("Create example file") {
+                    create_example(state);
+                }
+            }
+            result_limit_note(ui, state, tree);
+            state.results_shown = 0;
+            match state.config.view_mode {
+                ViewMode::Tree => {
+                    let expand = std::mem::take(&mut state.expand_top_level);
+                    // Nodes get separate open states while searching, so the ones opened
+                    // to show matches are closed again once the search is cleared
+                    let _scope = state.search.is_active().then(|| ui.push_id("search"));
+                    handle_nodes(ui, state, &tree.nodes, expand)
+                }
+                ViewMode::Table => handle_table_view(ui, state, tree),
+                ViewMode::Flat => handle_flat_view(ui, state, tree),
+            }
+            state.reveal = None;
+        });
+
+    #[cfg(feature = "mumble-link")]
+    if state.picker.draw(ui, tree, layout) {
+        state.reload_requested = true;
+    }
+
+    state.compare.draw(ui, &state.config, layout);
+    state.raw_json.draw(ui, layout);
+    if let Some(status) = state.report.draw(ui, tree, &state.data_dir, layout) {
+        state.status = Some(status);
+    }
+    state.diff.draw(ui, &state.config, layout);
+    if let Some((objective, source)) = state.queue.draw(ui, state.config.accent(), layout) {
+        activate_objective(state, &objective, &source);
+    }
+    confirmation_modal(ui, state);
+    save_reorder_modal(ui, state);
+    copy_to_modal(ui, state, tree);
+    fps_overlay(ui, state);
+    keybind_cheatsheet(ui, state);
+
+    if let Some(config) = state.settings.draw(ui, &state.config, layout) {
+        if config.data_dir != state.config.data_dir {
+            state.data_dir = config.data_dir.clone();
+            state.reload_requested = true;
+        }
+        state.reload_requested |= config.needs_reload(&state.config);
+        state.config = config;
+        state.config.save();
+    }
+
+    if std::mem::take(&mut state.settings.reset_layout) {
+        state.reset_layout = true;
+        state.status = Some(match std::fs::remove_file(LAYOUT_PATH) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                format!("Could not delete {}: {}", LAYOUT_PATH, e)
+            }
+            _ => "Reset the window layout".to_string(),
         });
+    }
+
+    // A hint edit whose row scrolled out, collapsed or was filtered away is cancelled
+    if let Some(edit) = &mut state.hint_edit {
+        if !std::mem::take(&mut edit.drawn) {
+            state.hint_edit = None;
+        }
+    }
+    // Auto-reload and re-reads of truncated files wait for an unsaved objective order or
+    // hint, which a reload would discard
+    let auto_reload_due = state.reorder.is_none()
+        && state.hint_edit.is_none()
+        && (tree.retry_due()
+            || state.config.auto_reload_secs.is_some_and(|secs| {
+                secs > 0 && state.last_reload.elapsed() >= Duration::from_secs(secs)
+            }));
+    if state.reload_requested {
+        *tree = load_tree(&state.data_dir, &state.config);
+        state.tree_revision += 1;
+        state.icons.clear();
+        state.reorder = None;
+        state.hint_edit = None;
+        // The data directory may have changed or lost the scope directory
+        let kept = |dir: &PathBuf| dir.starts_with(&state.data_dir) && dir.is_dir();
+        state.search.scope = state.search.scope.take().filter(kept);
+        state.current_dir = state.current_dir.take().filter(kept);
+        state.expand_top_level = state.config.expand_top_level;
+        state.reload_requested = false;
+        state.last_reload = Instant::now();
+    } else if auto_reload_due {
+        // Only files that changed since the last scan, or were truncated, are parsed again
+        let refresh = tree.refresh(&state.data_dir);
+        check_positions(tree, &state.config, &state.data_dir);
+        if !refresh.is_empty() {
+            state.tree_revision += 1;
+            state.icons.clear();
+            state.status = Some(format!("Reloaded {}", refresh));
+        }
+        state.last_reload = Instant::now();
+    }
+}
+
+/// Loads the data directory, lazily if it is large enough, prints its summary line and
+/// checks the positions
+fn load_tree(data_dir: &Path, config: &Config) -> DataTree {
+    let lazy = config
+        .lazy_load_files
+        .is_some_and(|files| data::count_data_files(data_dir) >= files);
+    let mut tree = if lazy {
+        DataTree::load_lazy(data_dir)
+    } else {
+        DataTree::load(data_dir)
+    };
+    println!("Loaded {}: {}", data_dir.display(), tree.summary);
+    check_positions(&mut tree, config, data_dir);
+    tree
+}
+
+/// Reports positions outside the configured map bounds, identical positions and objectives
+/// on another map than their file's path implies
+fn check_positions(tree: &mut DataTree, config: &Config, data_dir: &Path) {
+    tree.clear_checks();
+    tree.check_map_bounds(&config.map_bounds);
+    tree.check_duplicate_positions(config.duplicates_across_files);
+    if !config.map_path_pattern.is_empty() {
+        match Regex::new(&config.map_path_pattern) {
+            Ok(pattern) => tree.check_path_maps(&pattern, data_dir),
+            Err(e) => eprintln!("Error parsing map_path_pattern: {}", e),
+        }
+    }
 }
 
 /// Sets up the window and returns the event loop and display
-fn setup_window() -> (glutin::event_loop::EventLoop<()>, glium::Display) {
+fn setup_window(
+    config: &Config,
+    visible: bool,
+) -> (glutin::event_loop::EventLoop<()>, glium::Display) {
     let event_loop = glutin::event_loop::EventLoop::new();
-    let wb = glutin::window::WindowBuilder::new()
+    let [min_width, min_height] = config.min_window_size;
+    let mut wb = glutin::window::WindowBuilder::new()
         .with_title("GW2 Teleport")
-        .with_inner_size(glutin::dpi::LogicalSize::new(1024f64, 768f64));
+        .with_inner_size(glutin::dpi::LogicalSize::new(
+            min_width.max(1024),
+            min_height.max(768),
+        ))
+        .with_min_inner_size(glutin::dpi::LogicalSize::new(min_width, min_height))
+        .with_visible(visible);
+    match config.window_mode {
+        WindowMode::Windowed => {}
+        WindowMode::Maximized => wb = wb.with_maximized(true),
+        WindowMode::Fullscreen => match event_loop.primary_monitor() {
+            Some(monitor) => {
+                wb = wb.with_fullscreen(Some(glutin::window::Fullscreen::Borderless(Some(monitor))));
+            }
+            None => {
+                eprintln!("No monitor information available, starting maximized instead");
+                wb = wb.with_maximized(true);
+            }
+        },
+    }
     let cb = glutin::ContextBuilder::new().with_vsync(true);
-    let display = glium::Display::new(wb, cb, &event_loop).unwrap();
+    let preferred = config.gpu.dri_prime().and_then(|dri_prime| {
+        // Mesa reads DRI_PRIME when the context is created; no other thread runs yet
+        let previous = std::env::var_os("DRI_PRIME");
+        std::env::set_var("DRI_PRIME", dri_prime);
+        let display = glium::Display::new(wb.clone(), cb.clone(), &event_loop);
+        match previous {
+            Some(previous) => std::env::set_var("DRI_PRIME", previous),
+            None => std::env::remove_var("DRI_PRIME"),
+        }
+        display
+            .inspect_err(|e| {
+                eprintln!(
+                    "Error creating a display on the {} GPU, using the default: {}",
+                    config.gpu.label().to_lowercase(),
+                    e
+                )
+            })
+            .ok()
+    });
+    let display = preferred.unwrap_or_else(|| glium::Display::new(wb, cb, &event_loop).unwrap());
+    println!(
+        "Rendering with {} ({})",
+        display.get_opengl_renderer_string(),
+        display.get_opengl_vendor_string()
+    );
     (event_loop, display)
 }
 
+/// Replaces the font atlas with the default font at `font_size` points
+fn load_fonts(imgui: &mut Context, font_size: f32, hidpi_factor: f64) {
+    let size_pixels = (font_size as f64 * hidpi_factor) as f32;
+    imgui.fonts().clear();
+    imgui.fonts().add_font(&[
+        imgui::FontSource::DefaultFontData {
+            config: Some(imgui::FontConfig {
+                size_pixels,
+                ..Default::default()
+            }),
+        },
+    ]);
+}
+
+/// Makes imgui keep the window layout in `LAYOUT_PATH`, or nowhere
+fn set_layout_file(imgui: &mut Context, save_layout: bool) {
+    imgui.set_ini_filename(save_layout.then(|| PathBuf::from(LAYOUT_PATH)));
+}
+
+/// Writes what is still only in memory before the app exits. Settings, favorites and usage
+/// counts are saved as they change; the layout is otherwise only saved every few seconds.
+fn shutdown(imgui: &mut Context, state: &AppState) {
+    if state.config.save_layout {
+        let mut layout = String::new();
+        imgui.save_ini_settings(&mut layout);
+        if let Err(e) = std::fs::write(LAYOUT_PATH, layout) {
+            eprintln!("Error saving {}: {}", LAYOUT_PATH, e);
+        }
+    }
+    // Reorders and hint edits are only written once confirmed, so they are dropped
+    if let Some(reorder) = &state.reorder {
+        eprintln!("Discarded the unsaved objective order of {}", reorder.path.display());
+    }
+    if let Some(edit) = &state.hint_edit {
+        eprintln!("Discarded the unsaved hint of {}", edit.name);
+    }
+}
+
 /// Sets up imgui and returns the imgui context, platform, and renderer
-fn setup_imgui(display: &glium::Display) -> (Context, WinitPlatform, Renderer) {
+fn setup_imgui(display: &glium::Display, config: &Config) -> (Context, WinitPlatform, Renderer) {
     let mut imgui = Context::create();
-    imgui.set_ini_filename(None);
+    set_layout_file(&mut imgui, config.save_layout);
+    imgui.io_mut().config_flags |= imgui::ConfigFlags::NAV_ENABLE_KEYBOARD;
 
     let mut platform = WinitPlatform::init(&mut imgui);
     platform.attach_window(
@@ -111,53 +2571,222 @@ fn setup_imgui(display: &glium::Display) -> (Context, WinitPlatform, Renderer) {
         HiDpiMode::Default,
     );
 
-    let hidpi_factor = platform.hidpi_factor();
-    let font_size = (13.0 * hidpi_factor) as f32;
-    imgui.fonts().add_font(&[
-        imgui::FontSource::DefaultFontData {
-            config: Some(imgui::FontConfig {
-                size_pixels: font_size,
-                ..Default::default()
-            }),
-        },
-    ]);
+    load_fonts(&mut imgui, config.font_size, platform.hidpi_factor());
+    config.theme.apply(imgui.style_mut());
 
     let renderer = Renderer::init(&mut imgui, display).unwrap();
     (imgui, platform, renderer)
 }
 
+/// Builds the UI for one frame and renders it onto `target`
+fn draw_frame<S: Surface>(
+    imgui: &mut Context,
+    platform: &mut WinitPlatform,
+    renderer: &mut Renderer,
+    display: &glium::Display,
+    state: &mut AppState,
+    tree: &mut DataTree,
+    target: &mut S,
+) {
+    let ui = imgui.frame();
+    #[cfg(debug_assertions)]
+    let started = Instant::now();
+    teleport_window(ui, state, tree);
+    #[cfg(debug_assertions)]
+    {
+        let elapsed = started.elapsed();
+        if elapsed > FRAME_BUDGET {
+            eprintln!(
+                "Building the UI took {:?}, over the {:?} frame budget; \
+                 look for blocking file system access such as directory scans in the frame",
+                elapsed, FRAME_BUDGET
+            );
+        }
+    }
+
+    let [r, g, b, a] = state.config.theme.clear_color();
+    target.clear_color_srgb(r, g, b, a);
+    platform.prepare_render(ui, display.gl_window().window());
+    let draw_data = imgui.render();
+    renderer.render(target, draw_data).expect("Rendering failed");
+}
+
+/// Renders a few frames offscreen, so auto-sized windows and icons settle,
+/// and saves the last one as an image
+fn render_screenshot(
+    path: &Path,
+    imgui: &mut Context,
+    platform: &mut WinitPlatform,
+    renderer: &mut Renderer,
+    display: &glium::Display,
+    state: &mut AppState,
+    tree: &mut DataTree,
+) -> Result<(), String> {
+    let (width, height) = display.get_framebuffer_dimensions();
+    let texture = glium::Texture2d::empty(display, width, height).map_err(|e| e.to_string())?;
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(display, &texture)
+        .map_err(|e| e.to_string())?;
+    for _ in 0..3 {
+        imgui.io_mut().update_delta_time(Duration::from_secs_f32(1.0 / 60.0));
+        platform
+            .prepare_frame(imgui.io_mut(), display.gl_window().window())
+            .map_err(|e| e.to_string())?;
+        draw_frame(imgui, platform, renderer, display, state, tree, &mut framebuffer);
+        state.icons.load_pending(display, renderer);
+    }
+
+    let pixels: glium::texture::RawImage2d<u8> = texture.read();
+    let image = image::RgbaImage::from_raw(width, height, pixels.data.into_owned())
+        .ok_or("Rendered image has an unexpected size")?;
+    // OpenGL stores rows bottom to top
+    image::imageops::flip_vertical(&image)
+        .save(path)
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
 fn main() {
-    let (event_loop, display) = setup_window();
-    let (mut imgui, mut platform, mut renderer) = setup_imgui(&display);
+    let args = cli::Args::parse();
+    let screenshot = args.screenshot.clone();
+    let select = args.select.clone();
+    let config = Config::load();
+    crash::install_panic_hook(config.crash_dialog);
+
+    let (event_loop, display) = setup_window(&config, screenshot.is_none());
+    let (mut imgui, mut platform, mut renderer) = setup_imgui(&display, &config);
+    let mut applied_theme = config.theme;
+    let mut applied_font_size = config.font_size;
+    let mut applied_save_layout = config.save_layout;
+    let mut applied_min_size = config.min_window_size;
     let mut last_frame = Instant::now();
+    let mut state = AppState::new(config, args);
+    state.config.prune_recent_dirs();
+    let mut tree = load_tree(&state.data_dir, &state.config);
+    // Screenshots leave the config, the HTTP port and selections alone
+    if let Some(path) = screenshot {
+        let result = render_screenshot(
+            &path,
+            &mut imgui,
+            &mut platform,
+            &mut renderer,
+            &display,
+            &mut state,
+            &mut tree,
+        );
+        match result {
+            Ok(()) => println!("Saved screenshot to {}", path.display()),
+            Err(e) => {
+                eprintln!("Error rendering screenshot: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    state.config.last_launch = Some(now.as_secs());
+    state.config.save();
+    #[cfg(feature = "http-server")]
+    {
+        let proxy = event_loop.create_proxy();
+        let wake = move || {
+            // The loop is gone once the app shuts down, leaving nobody to wake
+            let _ = proxy.send_event(());
+        };
+        state.api = http::start(state.config.http_port, wake)
+            .map_err(|e| eprintln!("Error starting HTTP server: {}", e))
+            .ok();
+    }
+    for name in select {
+        if let Err(e) = select_by_name(&mut state, &tree, &name) {
+            eprintln!("Error selecting {}: {}", name, e);
+        }
+    }
 
+    // Ctrl+C in the terminal closes the window like the close button, so state is flushed
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&interrupted);
+    let proxy = event_loop.create_proxy();
+    let handler = move || {
+        flag.store(true, Ordering::SeqCst);
+        // Wakes an idle loop so the window closes at once; it may already be gone
+        let _ = proxy.send_event(());
+    };
+    if let Err(e) = ctrlc::set_handler(handler) {
+        eprintln!("Error installing the Ctrl+C handler: {}", e);
+    }
+
+    // Frames are only drawn after input, on timers and while something animates
+    let mut frames_left = FRAMES_AFTER_INPUT;
     event_loop.run(move |event, _, control_flow| {
         match event {
-            glutin::event::Event::NewEvents(_) => {
+            glutin::event::Event::NewEvents(cause) => {
                 imgui.io_mut().update_delta_time(last_frame.elapsed());
                 last_frame = Instant::now();
+                if matches!(cause, glutin::event::StartCause::ResumeTimeReached { .. }) {
+                    frames_left = frames_left.max(1);
+                }
             }
             glutin::event::Event::MainEventsCleared => {
-                let gl_window = display.gl_window();
-                platform
-                    .prepare_frame(imgui.io_mut(), gl_window.window())
-                    .unwrap();
-                gl_window.window().request_redraw();
+                if interrupted.load(Ordering::SeqCst) {
+                    *control_flow = glutin::event_loop::ControlFlow::Exit;
+                }
+                if frames_left > 0 || state.is_animating() {
+                    let gl_window = display.gl_window();
+                    platform
+                        .prepare_frame(imgui.io_mut(), gl_window.window())
+                        .unwrap();
+                    gl_window.window().request_redraw();
+                }
+            }
+            glutin::event::Event::RedrawEventsCleared => {
+                if *control_flow != glutin::event_loop::ControlFlow::Exit {
+                    *control_flow = if frames_left > 0 || state.is_animating() {
+                        glutin::event_loop::ControlFlow::Poll
+                    } else {
+                        glutin::event_loop::ControlFlow::WaitUntil(state.next_wake(&tree))
+                    };
+                }
             }
             glutin::event::Event::RedrawRequested(_) => {
-                let ui = imgui.frame();
-                teleport_window(&ui);
+                if state.config.theme != applied_theme {
+                    applied_theme = state.config.theme;
+                    applied_theme.apply(imgui.style_mut());
+                }
+                if state.config.font_size != applied_font_size {
+                    applied_font_size = state.config.font_size;
+                    load_fonts(&mut imgui, applied_font_size, platform.hidpi_factor());
+                    renderer
+                        .reload_font_texture(&mut imgui)
+                        .expect("Failed to reload font texture");
+                }
+
+                if state.config.min_window_size != applied_min_size {
+                    applied_min_size = state.config.min_window_size;
+                    let [width, height] = applied_min_size;
+                    let min_size = glutin::dpi::LogicalSize::new(width, height);
+                    display.gl_window().window().set_min_inner_size(Some(min_size));
+                }
+                if state.config.save_layout != applied_save_layout {
+                    applied_save_layout = state.config.save_layout;
+                    set_layout_file(&mut imgui, applied_save_layout);
+                }
 
-                let gl_window = display.gl_window();
                 let mut target = display.draw();
-                target.clear_color_srgb(0.2, 0.2, 0.2, 1.0);
-                
-                platform.prepare_render(&ui, gl_window.window());
-                let draw_data = imgui.render();
-                renderer
-                    .render(&mut target, draw_data)
-                    .expect("Rendering failed");
+                draw_frame(
+                    &mut imgui,
+                    &mut platform,
+                    &mut renderer,
+                    &display,
+                    &mut state,
+                    &mut tree,
+                    &mut target,
+                );
                 target.finish().expect("Failed to swap buffers");
+                frames_left = frames_left.saturating_sub(1);
+                // New icons show up in the next frame
+                if state.icons.load_pending(&display, &mut renderer) {
+                    frames_left = frames_left.max(1);
+                }
             }
             glutin::event::Event::WindowEvent {
                 event: glutin::event::WindowEvent::CloseRequested,
@@ -165,7 +2794,12 @@ fn main() {
             } => {
                 *control_flow = glutin::event_loop::ControlFlow::Exit;
             }
+            glutin::event::Event::LoopDestroyed => shutdown(&mut imgui, &state),
             event => {
+                // Raw device events also arrive for input outside the window
+                if !matches!(event, glutin::event::Event::DeviceEvent { .. }) {
+                    frames_left = FRAMES_AFTER_INPUT;
+                }
                 let gl_window = display.gl_window();
                 platform.handle_event(imgui.io_mut(), gl_window.window(), &event);
             }
@@ -0,0 +1,113 @@
+//! Reads the player's position from the MumbleLink shared memory that Guild Wars 2
+//! publishes for positional audio, so no game process memory is touched.
+
+/// Guild Wars 2 positions are in inches, MumbleLink reports meters
+const METERS_TO_GAME_UNITS: f32 = 39.3701;
+
+/// Layout of the MumbleLink shared memory block
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(dead_code)] // Mirrors the C layout, not every field is read
+struct LinkedMem {
+    ui_version: u32,
+    ui_tick: u32,
+    avatar_position: [f32; 3],
+    avatar_front: [f32; 3],
+    avatar_top: [f32; 3],
+    name: [u16; 256],
+    camera_position: [f32; 3],
+    camera_front: [f32; 3],
+    camera_top: [f32; 3],
+    identity: [u16; 256],
+    context_len: u32,
+    /// Guild Wars 2 stores its `MumbleContext` here
+    context: [u8; 256],
+    description: [u16; 2048],
+}
+
+/// Offset of the map ID inside the Guild Wars 2 `MumbleContext`
+const CONTEXT_MAP_ID_OFFSET: usize = 28;
+
+/// The player's state as last published by the game
+pub struct PlayerState {
+    /// Avatar position in game units
+    pub pos: [f32; 3],
+    pub map: i32,
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::LinkedMem;
+    use std::ffi::c_void;
+
+    type Handle = *mut c_void;
+
+    const FILE_MAP_READ: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenFileMappingW(desired_access: u32, inherit_handle: i32, name: *const u16) -> Handle;
+        fn MapViewOfFile(
+            mapping: Handle,
+            desired_access: u32,
+            offset_high: u32,
+            offset_low: u32,
+            bytes: usize,
+        ) -> *mut c_void;
+        fn UnmapViewOfFile(base: *const c_void) -> i32;
+        fn CloseHandle(handle: Handle) -> i32;
+    }
+
+    /// Copies the current contents of the MumbleLink block
+    pub fn read_link() -> Result<LinkedMem, String> {
+        let name: Vec<u16> = "MumbleLink".encode_utf16().chain(Some(0)).collect();
+        // SAFETY: the view is checked for null, sized for `LinkedMem` and only read
+        // through an unaligned copy before being unmapped
+        unsafe {
+            let mapping = OpenFileMappingW(FILE_MAP_READ, 0, name.as_ptr());
+            if mapping.is_null() {
+                return Err("Guild Wars 2 is not running".to_string());
+            }
+            let size = std::mem::size_of::<LinkedMem>();
+            let view = MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, size);
+            if view.is_null() {
+                CloseHandle(mapping);
+                return Err("Could not map the MumbleLink memory".to_string());
+            }
+            let link = std::ptr::read_unaligned(view as *const LinkedMem);
+            UnmapViewOfFile(view);
+            CloseHandle(mapping);
+            Ok(link)
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use super::LinkedMem;
+
+    pub fn read_link() -> Result<LinkedMem, String> {
+        Err("Reading the game position is only supported on Windows".to_string())
+    }
+}
+
+/// Decodes a nul-terminated UTF-16 buffer
+fn wide_to_string(wide: &[u16]) -> String {
+    let len = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    String::from_utf16_lossy(&wide[..len])
+}
+
+/// Reads the player's position and map from the running game
+pub fn read_player_state() -> Result<PlayerState, String> {
+    let link = platform::read_link()?;
+    if link.ui_tick == 0 || wide_to_string(&link.name) != "Guild Wars 2" {
+        return Err("Guild Wars 2 is not running or not in a map".to_string());
+    }
+
+    let map_bytes = &link.context[CONTEXT_MAP_ID_OFFSET..CONTEXT_MAP_ID_OFFSET + 4];
+    let map = u32::from_le_bytes(map_bytes.try_into().expect("slice is four bytes"));
+    Ok(PlayerState {
+        pos: link.avatar_position.map(|axis| axis * METERS_TO_GAME_UNITS),
+        map: map as i32,
+    })
+}
@@ -0,0 +1,78 @@
+use crate::{data::DataTree, edit, mumble};
+use imgui::{Condition, Ui};
+use serde_json::json;
+
+/// The "Position picker" window, which saves the player's position as a new objective
+#[derive(Default)]
+pub struct PositionPicker {
+    pub open: bool,
+    name: String,
+    /// Index into `DataTree::locations` of the file to append to
+    target: usize,
+    status: Option<String>,
+}
+
+impl PositionPicker {
+    /// Draws the window; returns true if a file was changed and the tree needs reloading.
+    /// `layout` is when the default position and size apply
+    pub fn draw(&mut self, ui: &Ui, tree: &DataTree, layout: Condition) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let mut open = self.open;
+        let mut changed = false;
+        ui.window("Position picker")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([360.0, 0.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                let player = mumble::read_player_state();
+                match &player {
+                    Ok(player) => ui.text(format!(
+                        "Map {} at [{:.2}, {:.2}, {:.2}]",
+                        player.map, player.pos[0], player.pos[1], player.pos[2]
+                    )),
+                    Err(e) => ui.text_disabled(e),
+                }
+
+                let locations = tree.locations();
+                if locations.is_empty() {
+                    ui.text_disabled("No location files loaded");
+                    return;
+                }
+                self.target = self.target.min(locations.len() - 1);
+                ui.combo("File", &mut self.target, &locations, |location| {
+                    location.path.display().to_string().into()
+                });
+                ui.input_text("Name", &mut self.name).build();
+
+                let can_save = player.is_ok() && !self.name.trim().is_empty();
+                ui.disabled(!can_save, || {
+                    if ui.button("Save current position") {
+                        if let Ok(player) = &player {
+                            let path = &locations[self.target].path;
+                            let objective = json!({
+                                "name": self.name.trim(),
+                                "map": player.map,
+                                "pos": player.pos,
+                            });
+                            self.status = Some(match edit::append_objective(path, objective) {
+                                Ok(_) => {
+                                    changed = true;
+                                    format!("Saved {} to {}", self.name.trim(), path.display())
+                                }
+                                Err(e) => e,
+                            });
+                        }
+                    }
+                });
+                if let Some(status) = &self.status {
+                    ui.text_wrapped(status);
+                }
+            });
+        self.open = open;
+        changed
+    }
+}
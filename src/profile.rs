@@ -0,0 +1,99 @@
+use crate::{
+    data::ObjectiveKey,
+    favorites::Favorites,
+    usage::{Usage, UsageEntry},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Favorites and usage counts bundled into one portable file
+#[derive(Serialize, Deserialize)]
+struct Profile {
+    #[serde(default)]
+    favorites: Vec<ObjectiveKey>,
+    #[serde(default)]
+    usage: Vec<UsageEntry>,
+}
+
+/// How imported data is combined with the current data
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Keep both; favorites are combined and usage counts added up
+    Merge,
+    /// Drop the current data in favor of the imported set
+    Replace,
+}
+
+/// Writes the favorites and usage counts to `path`
+pub fn export(path: &Path, favorites: &Favorites, usage: &Usage) -> Result<(), String> {
+    let profile = Profile {
+        favorites: favorites.keys().cloned().collect(),
+        usage: usage.entries(),
+    };
+    let content = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Reads a file written by `export` into the favorites and usage counts
+pub fn import(
+    path: &Path,
+    favorites: &mut Favorites,
+    usage: &mut Usage,
+    mode: ImportMode,
+) -> Result<(), String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let profile: Profile = serde_json::from_str(&content)
+        .map_err(|e| format!("Could not parse {}: {}", path.display(), e))?;
+    let replace = mode == ImportMode::Replace;
+    favorites.merge(profile.favorites, replace);
+    usage.merge(profile.usage, replace);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::TempDir;
+
+    fn key(name: &str) -> ObjectiveKey {
+        ObjectiveKey {
+            name: name.to_string(),
+            map: Some(15),
+        }
+    }
+
+    /// Returns favorites and usage counts for `names`, each selected `count` times
+    fn profile(names: &[&str], count: u32) -> (Favorites, Usage) {
+        let (mut favorites, mut usage) = (Favorites::default(), Usage::default());
+        for name in names {
+            favorites.toggle(key(name));
+            for _ in 0..count {
+                usage.record(key(name));
+            }
+        }
+        (favorites, usage)
+    }
+
+    fn names(favorites: &Favorites) -> Vec<&str> {
+        favorites.keys().map(|key| key.name.as_str()).collect()
+    }
+
+    #[test]
+    fn imports_by_merging_or_replacing() {
+        let dir = TempDir::new("profile");
+        let path = dir.join("profile.json");
+        let (favorites, usage) = profile(&["A", "B"], 2);
+        export(&path, &favorites, &usage).unwrap();
+
+        let (mut favorites, mut usage) = profile(&["B", "C"], 1);
+        import(&path, &mut favorites, &mut usage, ImportMode::Merge).unwrap();
+        assert_eq!(names(&favorites), ["A", "B", "C"]);
+        assert_eq!([key("A"), key("B"), key("C")].map(|k| usage.count(&k)), [2, 3, 1]);
+
+        let (mut favorites, mut usage) = profile(&["B", "C"], 1);
+        import(&path, &mut favorites, &mut usage, ImportMode::Replace).unwrap();
+        assert_eq!(names(&favorites), ["A", "B"]);
+        assert_eq!([key("A"), key("B"), key("C")].map(|k| usage.count(&k)), [2, 2, 0]);
+    }
+}
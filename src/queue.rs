@@ -0,0 +1,158 @@
+use crate::data::Position;
+use imgui::{Condition, ProgressBar, Ui};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// An objective waiting in the queue, copied so it survives reloads
+struct Queued {
+    objective: Position,
+    source: PathBuf,
+}
+
+/// The "Teleport queue" window, which runs the selection action for a list of objectives
+/// one step at a time
+pub struct QueueWindow {
+    pub open: bool,
+    items: Vec<Queued>,
+    /// Index of the next objective to run
+    next: usize,
+    /// Run the next step automatically every `interval_secs`
+    timer: bool,
+    interval_secs: i32,
+    last_step: Instant,
+}
+
+impl Default for QueueWindow {
+    fn default() -> Self {
+        QueueWindow {
+            open: false,
+            items: Vec::new(),
+            next: 0,
+            timer: false,
+            interval_secs: 10,
+            last_step: Instant::now(),
+        }
+    }
+}
+
+impl QueueWindow {
+    /// Appends `objective` to the queue and shows the window
+    pub fn push(&mut self, objective: &Position, source: &Path) {
+        self.items.push(Queued {
+            objective: objective.clone(),
+            source: source.to_path_buf(),
+        });
+        self.open = true;
+    }
+
+    /// When the timer runs the next step, if it is on and objectives are left
+    pub fn next_step_at(&self) -> Option<Instant> {
+        let interval = Duration::from_secs(self.interval_secs.max(1) as u64);
+        (self.timer && self.next < self.items.len()).then(|| self.last_step + interval)
+    }
+
+    /// Draws the window, marking the next objective in `accent`, and returns the objective to
+    /// run this frame with its source path
+    pub fn draw(
+        &mut self,
+        ui: &Ui,
+        accent: [f32; 4],
+        layout: Condition,
+    ) -> Option<(Position, PathBuf)> {
+        if !self.open {
+            return None;
+        }
+
+        let mut open = self.open;
+        let mut step = false;
+        ui.window("Teleport queue")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([320.0, 0.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                if self.items.is_empty() {
+                    ui.text_disabled("Right-click an objective and pick \"Add to queue\"");
+                    return;
+                }
+
+                let total = self.items.len();
+                let overlay = format!("{} of {} done", self.next, total);
+                ProgressBar::new(self.next as f32 / total as f32)
+                    .overlay_text(&overlay)
+                    .build(ui);
+
+                let mut action = None;
+                for (index, item) in self.items.iter().enumerate() {
+                    let _id = ui.push_id_usize(index);
+                    let label = format!("{}. {}", index + 1, item.objective.name);
+                    match index.cmp(&self.next) {
+                        std::cmp::Ordering::Less => ui.text_disabled(label),
+                        std::cmp::Ordering::Equal => ui.text_colored(accent, label),
+                        std::cmp::Ordering::Greater => ui.text(label),
+                    }
+                    ui.same_line();
+                    if ui.small_button("^") && index > 0 {
+                        action = Some((index, index - 1));
+                    }
+                    ui.same_line();
+                    if ui.small_button("v") && index + 1 < total {
+                        action = Some((index, index + 1));
+                    }
+                    ui.same_line();
+                    if ui.small_button("x") {
+                        action = Some((index, usize::MAX));
+                    }
+                }
+                match action {
+                    Some((index, usize::MAX)) => {
+                        self.items.remove(index);
+                        if index < self.next {
+                            self.next -= 1;
+                        }
+                    }
+                    Some((a, b)) => self.items.swap(a, b),
+                    None => {}
+                }
+                ui.separator();
+
+                let done = self.next >= self.items.len();
+                ui.disabled(done, || {
+                    if ui.button("Next") {
+                        step = true;
+                    }
+                });
+                ui.same_line();
+                if ui.button("Restart") {
+                    self.next = 0;
+                    self.last_step = Instant::now();
+                }
+                ui.same_line();
+                if ui.button("Clear") {
+                    self.items.clear();
+                    self.next = 0;
+                }
+
+                if ui.checkbox("Run on a timer", &mut self.timer) {
+                    self.last_step = Instant::now();
+                }
+                if self.timer {
+                    if ui.input_int("Interval (seconds)", &mut self.interval_secs).build() {
+                        self.interval_secs = self.interval_secs.max(1);
+                    }
+                    let interval = Duration::from_secs(self.interval_secs as u64);
+                    if !done && self.last_step.elapsed() >= interval {
+                        step = true;
+                    }
+                }
+            });
+        self.open = open;
+
+        let item = self.items.get(self.next).filter(|_| step)?;
+        self.next += 1;
+        self.last_step = Instant::now();
+        Some((item.objective.clone(), item.source.clone()))
+    }
+}
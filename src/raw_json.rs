@@ -0,0 +1,44 @@
+use crate::data::Location;
+use imgui::{Condition, Ui};
+use std::path::PathBuf;
+
+/// The "Parsed JSON" window, which shows a location as the app parsed it, after includes
+/// are merged and defaults filled in, to compare with the file on disk
+#[derive(Default)]
+pub struct RawJsonWindow {
+    pub open: bool,
+    path: PathBuf,
+    json: String,
+}
+
+impl RawJsonWindow {
+    /// Shows `location` serialized back to pretty-printed JSON
+    pub fn show(&mut self, location: &Location) {
+        self.path = location.path.clone();
+        self.json = serde_json::to_string_pretty(&location.data)
+            .unwrap_or_else(|e| format!("Could not serialize: {}", e));
+        self.open = true;
+    }
+
+    /// Draws the window; `layout` is when the default position and size apply
+    pub fn draw(&mut self, ui: &Ui, layout: Condition) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        ui.window("Parsed JSON")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([480.0, 420.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                ui.text(self.path.display().to_string());
+                ui.text_disabled("Includes are merged and icons and source lines are left out");
+                ui.input_text_multiline("##json", &mut self.json, [-1.0, -1.0])
+                    .read_only(true)
+                    .build();
+            });
+        self.open = open;
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{data::DataTree, export::escape_cell};
+use imgui::{Condition, TreeNodeFlags, Ui};
+use std::{fs, path::Path};
+
+/// Optional fields the report looks for, in column order
+const FIELDS: [&str; 3] = ["pos", "hint", "map"];
+
+/// An objective missing at least one optional field
+struct Gap {
+    name: String,
+    line: Option<usize>,
+    /// Which of `FIELDS` are missing
+    missing: [bool; 3],
+}
+
+/// The objectives of one file that are missing fields, with per-field counts
+struct FileGaps {
+    file: String,
+    gaps: Vec<Gap>,
+    counts: [usize; 3],
+}
+
+/// The "Missing metadata" window, which lists objectives without a position, a hint or,
+/// if any objective has one, a map, grouped by file with the most incomplete files first
+#[derive(Default)]
+pub struct ReportWindow {
+    pub open: bool,
+    files: Vec<FileGaps>,
+    /// Whether any objective has a map, so objectives without one are worth listing
+    uses_map: bool,
+    /// Files not parsed yet in a lazily loaded tree, which the report leaves out
+    pending: usize,
+}
+
+impl ReportWindow {
+    /// Builds the report from the loaded `tree` and shows the window
+    pub fn show(&mut self, tree: &DataTree, data_dir: &Path) {
+        let locations = tree.locations();
+        let objectives = || locations.iter().flat_map(|location| &location.data.objectives);
+        self.uses_map = objectives().any(|objective| objective.map.is_some());
+        self.pending = tree.summary.pending;
+        self.files = locations
+            .iter()
+            .map(|location| {
+                let file = location.path.strip_prefix(data_dir).unwrap_or(&location.path);
+                let mut counts = [0; 3];
+                let gaps = location
+                    .data
+                    .objectives
+                    .iter()
+                    .filter_map(|objective| {
+                        let missing = [
+                            objective.pos.is_none(),
+                            objective.hint.as_deref().is_none_or(str::is_empty),
+                            self.uses_map && objective.map.is_none(),
+                        ];
+                        for (count, missing) in counts.iter_mut().zip(missing) {
+                            *count += usize::from(missing);
+                        }
+                        missing.contains(&true).then(|| Gap {
+                            name: objective.name.clone(),
+                            line: objective.source.as_ref().map(|source| source.line),
+                            missing,
+                        })
+                    })
+                    .collect();
+                FileGaps {
+                    file: file.display().to_string(),
+                    gaps,
+                    counts,
+                }
+            })
+            .filter(|file| !file.gaps.is_empty())
+            .collect();
+        self.files.sort_by_key(|file| std::cmp::Reverse(file.counts.iter().sum::<usize>()));
+        self.open = true;
+    }
+
+    /// Fields the report lists, leaving `map` out when no objective has one
+    fn fields(&self) -> &'static [&'static str] {
+        if self.uses_map {
+            &FIELDS
+        } else {
+            &FIELDS[..2]
+        }
+    }
+
+    /// Names of the fields `gap` is missing, comma-separated
+    fn missing_list(&self, gap: &Gap) -> String {
+        let fields = self.fields().iter().zip(gap.missing);
+        let missing = fields.filter(|(_, missing)| *missing).map(|(field, _)| *field);
+        missing.collect::<Vec<_>>().join(", ")
+    }
+
+    /// Renders the report as CSV with a row per objective and a column per field
+    fn to_csv(&self) -> Result<String, String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut header = vec!["file".to_string(), "line".to_string(), "name".to_string()];
+        header.extend(self.fields().iter().map(|field| format!("missing_{}", field)));
+        writer.write_record(&header).map_err(|e| e.to_string())?;
+        for file in &self.files {
+            for gap in &file.gaps {
+                let line = gap.line.map(|line| line.to_string()).unwrap_or_default();
+                let mut record = vec![file.file.clone(), line, gap.name.clone()];
+                let missing = gap.missing.iter().take(self.fields().len());
+                record.extend(missing.map(|missing| if *missing { "yes" } else { "" }.to_string()));
+                writer.write_record(&record).map_err(|e| e.to_string())?;
+            }
+        }
+        let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+
+    /// Renders the report as Markdown with a section and a table per file
+    fn to_markdown(&self) -> String {
+        let mut text = String::from("# Missing metadata\n");
+        for file in &self.files {
+            let heading = format!("{} ({})", escape_cell(&file.file), self.summary(file));
+            text.push_str(&format!("\n## {}\n\n", heading));
+            text.push_str("| Line | Name | Missing |\n|---|---|---|\n");
+            for gap in &file.gaps {
+                let line = gap.line.map(|line| line.to_string()).unwrap_or_default();
+                let name = escape_cell(&gap.name);
+                text.push_str(&format!("| {} | {} | {} |\n", line, name, self.missing_list(gap)));
+            }
+        }
+        text
+    }
+
+    /// Per-field counts of `file`, such as "2 without pos, 5 without hint"
+    fn summary(&self, file: &FileGaps) -> String {
+        let counts = self.fields().iter().zip(file.counts);
+        let parts: Vec<_> = counts
+            .filter(|(_, count)| *count > 0)
+            .map(|(field, count)| format!("{} without {}", count, field))
+            .collect();
+        parts.join(", ")
+    }
+
+    /// Saves the report to a file the user picks, as CSV or Markdown by `markdown`
+    fn export(&self, markdown: bool) -> Option<String> {
+        let (filter, extension) = if markdown { ("Markdown", "md") } else { ("CSV", "csv") };
+        let path = rfd::FileDialog::new()
+            .add_filter(filter, &[extension])
+            .set_file_name(format!("missing-metadata.{}", extension))
+            .save_file()?;
+        let text = if markdown { Ok(self.to_markdown()) } else { self.to_csv() };
+        Some(
+            match text.and_then(|text| fs::write(&path, text).map_err(|e| e.to_string())) {
+                Ok(()) => format!("Exported the report to {}", path.display()),
+                Err(e) => format!("Error writing {}: {}", path.display(), e),
+            },
+        )
+    }
+
+    /// Draws the window and returns a status message after an export; `layout` is when the
+    /// default position and size apply
+    pub fn draw(
+        &mut self,
+        ui: &Ui,
+        tree: &DataTree,
+        data_dir: &Path,
+        layout: Condition,
+    ) -> Option<String> {
+        if !self.open {
+            return None;
+        }
+
+        let mut open = self.open;
+        let mut status = None;
+        ui.window("Missing metadata")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([420.0, 420.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                if ui.button("Refresh") {
+                    self.show(tree, data_dir);
+                }
+                ui.same_line();
+                if ui.button("Export CSV...") {
+                    status = self.export(false);
+                }
+                ui.same_line();
+                if ui.button("Export Markdown...") {
+                    status = self.export(true);
+                }
+
+                let mut totals = [0; 3];
+                for file in &self.files {
+                    for (total, count) in totals.iter_mut().zip(file.counts) {
+                        *total += count;
+                    }
+                }
+                let fields = self.fields().iter().zip(totals);
+                let totals = fields.map(|(field, total)| format!("{} without {}", total, field));
+                ui.text(totals.collect::<Vec<_>>().join(", "));
+                if self.pending > 0 {
+                    ui.text_disabled(format!("{} files not loaded yet are left out", self.pending));
+                }
+                ui.separator();
+
+                if self.files.is_empty() {
+                    ui.text_disabled("Every objective has all optional fields");
+                }
+                for (index, file) in self.files.iter().enumerate() {
+                    let _id = ui.push_id_usize(index);
+                    let label = format!("{} ({})", file.file, self.summary(file));
+                    if ui.collapsing_header(&label, TreeNodeFlags::empty()) {
+                        for gap in &file.gaps {
+                            match gap.line {
+                                Some(line) => ui.text(format!("{}: {}", line, gap.name)),
+                                None => ui.text(&gap.name),
+                            }
+                            ui.same_line();
+                            ui.text_disabled(format!("no {}", self.missing_list(gap)));
+                        }
+                    }
+                }
+            });
+        self.open = open;
+        status
+    }
+}
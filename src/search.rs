@@ -0,0 +1,118 @@
+use crate::data::{Location, Node, Position};
+use std::path::PathBuf;
+
+/// Which part of an objective matched the search query
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Match {
+    /// No query is active, everything is shown
+    Unfiltered,
+    Name,
+    /// One of the objective's aliases matched, which counts as a name match
+    Alias,
+    Hint,
+}
+
+/// The search box state
+#[derive(Default)]
+pub struct Search {
+    pub query: String,
+    /// Also match against objective hints
+    pub include_hints: bool,
+    /// Directory the results are limited to, with its subdirectories
+    pub scope: Option<PathBuf>,
+}
+
+impl Search {
+    /// Returns true if a query is entered
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    /// Returns true if `location` is inside the scope, or there is no scope or query
+    pub fn in_scope(&self, location: &Location) -> bool {
+        !self.is_active() || self.scope.as_ref().is_none_or(|dir| location.path.starts_with(dir))
+    }
+
+    /// Returns how `objective` matches the query, or `None` if it is filtered out
+    pub fn match_objective(&self, objective: &Position) -> Option<Match> {
+        if !self.is_active() {
+            return Some(Match::Unfiltered);
+        }
+
+        let query = self.query.trim().to_lowercase();
+        if objective.name.to_lowercase().contains(&query) {
+            return Some(Match::Name);
+        }
+        if objective.aliases.iter().any(|alias| alias.to_lowercase().contains(&query)) {
+            return Some(Match::Alias);
+        }
+        if self.include_hints {
+            if let Some(hint) = &objective.hint {
+                if hint.to_lowercase().contains(&query) {
+                    return Some(Match::Hint);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns true if any objective below `node` passes the query and `visible`
+    pub fn node_has_match(
+        &self,
+        node: &Node,
+        visible: &impl Fn(&Location, &Position) -> bool,
+    ) -> bool {
+        match node {
+            Node::Directory { children, .. } => {
+                children.iter().any(|child| self.node_has_match(child, visible))
+            }
+            Node::Location(location) => location.data.objectives.iter().any(|objective| {
+                visible(location, objective) && self.match_objective(objective).is_some()
+            }),
+            // Files that are not loaded yet cannot be searched
+            Node::Pending { .. } => false,
+        }
+    }
+}
+
+/// Minimum and maximum per axis that positions must fall within
+#[derive(Default, Clone, PartialEq)]
+pub struct RangeFilter {
+    /// Text of the min and max inputs for each displayed coordinate; empty inputs are
+    /// unbounded
+    pub inputs: [[String; 2]; 3],
+    bounds: [[Option<f32>; 2]; 3],
+}
+
+impl RangeFilter {
+    /// Parses the inputs after an edit; text that is not a number counts as empty
+    pub fn update(&mut self) {
+        for (bounds, inputs) in self.bounds.iter_mut().zip(&self.inputs) {
+            for (bound, input) in bounds.iter_mut().zip(inputs) {
+                *bound = input.trim().parse().ok();
+            }
+        }
+    }
+
+    /// Returns true if any bound is set
+    pub fn is_active(&self) -> bool {
+        self.bounds.iter().flatten().any(Option::is_some)
+    }
+
+    /// Returns true if the filter is off or `pos` lies within every set bound
+    pub fn contains(&self, pos: Option<[f32; 3]>) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let Some(pos) = pos else {
+            return false;
+        };
+        pos.iter().zip(&self.bounds).all(|(&value, [min, max])| {
+            min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+        })
+    }
+
+    pub fn clear(&mut self) {
+        *self = RangeFilter::default();
+    }
+}
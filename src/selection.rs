@@ -0,0 +1,71 @@
+use crate::data::{ObjectiveKey, Position};
+use std::path::{Path, PathBuf};
+
+/// Objectives picked with Ctrl and Shift clicks for batch actions, copied so they survive
+/// reloads and kept in the order they were picked
+#[derive(Default)]
+pub struct MultiSelection {
+    items: Vec<(Position, PathBuf)>,
+    /// Objective a Shift click selects from, the last one clicked without Shift
+    anchor: Option<ObjectiveKey>,
+}
+
+impl MultiSelection {
+    pub fn contains(&self, key: &ObjectiveKey) -> bool {
+        self.items.iter().any(|(objective, _)| objective.key() == *key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Selected objectives with their source paths, in the order they were picked
+    pub fn items(&self) -> impl Iterator<Item = (&Position, &Path)> {
+        self.items.iter().map(|(objective, source)| (objective, source.as_path()))
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Handles a plain click, which drops the selection and starts a new range at `objective`
+    pub fn click(&mut self, objective: &Position) {
+        self.items.clear();
+        self.anchor = Some(objective.key());
+    }
+
+    /// Handles a Ctrl click, adding or removing `objective`
+    pub fn toggle(&mut self, objective: &Position, source: &Path) {
+        let key = objective.key();
+        match self.items.iter().position(|(selected, _)| selected.key() == key) {
+            Some(index) => {
+                self.items.remove(index);
+            }
+            None => self.items.push((objective.clone(), source.to_path_buf())),
+        }
+        self.anchor = Some(key);
+    }
+
+    /// Handles a Shift click on `view[clicked]`, selecting every objective of `view` between
+    /// it and the anchor; an anchor outside `view` selects the clicked objective only
+    pub fn select_range(&mut self, view: &[(&Position, &Path)], clicked: usize) {
+        let found = self
+            .anchor
+            .as_ref()
+            .and_then(|anchor| view.iter().position(|(objective, _)| objective.key() == *anchor));
+        if found.is_none() {
+            self.anchor = Some(view[clicked].0.key());
+        }
+        let anchor = found.unwrap_or(clicked);
+        let range = anchor.min(clicked)..=anchor.max(clicked);
+        for &(objective, source) in &view[range] {
+            if !self.contains(&objective.key()) {
+                self.items.push((objective.clone(), source.to_path_buf()));
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{config::Config, data::Position};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Appends a selected objective to the configured selections log, if there is one
+pub fn append(config: &Config, objective: &Position) {
+    if config.selection_log.is_empty() {
+        return;
+    }
+    let path = Path::new(&config.selection_log);
+    if let Some(max_kb) = config.selection_log_max_kb {
+        rotate(path, max_kb);
+    }
+
+    let map = objective.map.map_or_else(|| "-".to_string(), |map| map.to_string());
+    let pos = objective
+        .pos
+        .map_or_else(|| "-".to_string(), |pos| format!("[{}]", config.format_output(pos)));
+    // Tabs and line breaks in the name would split the record
+    let name = objective.name.replace(['\t', '\r', '\n'], " ");
+    let line = format!("{}\t{}\t{}\t{}\n", timestamp(SystemTime::now()), name, map, pos);
+    let logged = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+    if let Err(e) = logged {
+        eprintln!("Error writing {}: {}", path.display(), e);
+    }
+}
+
+/// Replaces the configured last selection file, if there is one, with `objective` as a
+/// single line of JSON; written to a temporary file first so readers never see half of it
+pub fn write_last(config: &Config, objective: &Position) {
+    if config.selection_file.is_empty() {
+        return;
+    }
+    let path = Path::new(&config.selection_file);
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let written = serde_json::to_string(objective)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&temp, json + "\n").map_err(|e| e.to_string()))
+        .and_then(|_| fs::rename(&temp, path).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        eprintln!("Error writing {}: {}", path.display(), e);
+    }
+}
+
+/// Moves the log to `<path>.1`, replacing an older one, once it reaches `max_kb`
+fn rotate(path: &Path, max_kb: u64) {
+    let size = fs::metadata(path).map_or(0, |metadata| metadata.len());
+    if size < max_kb.saturating_mul(1024) {
+        return;
+    }
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(".1");
+    if let Err(e) = fs::rename(path, &rotated) {
+        eprintln!("Error rotating {}: {}", path.display(), e);
+    }
+}
+
+/// Formats `time` as `YYYY-MM-DD HH:MM:SS` in UTC
+fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+        - day_of_era / 146_096)
+        / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::TempDir;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_timestamps() {
+        let at = |secs| timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01 00:00:00");
+        assert_eq!(at(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(at(1_791_985_530), "2026-10-14 13:45:30");
+    }
+
+    #[test]
+    fn rotates_at_the_size_limit() {
+        let dir = TempDir::new("selection-log");
+        let path = dir.join("selections.log");
+        let rotated = dir.join("selections.log.1");
+        fs::write(&path, vec![b'a'; 1023]).unwrap();
+        rotate(&path, 1);
+        assert!(path.exists() && !rotated.exists());
+        fs::write(&path, vec![b'b'; 1024]).unwrap();
+        rotate(&path, 1);
+        assert!(!path.exists());
+        assert_eq!(fs::read(&rotated).unwrap(), vec![b'b'; 1024]);
+    }
+
+    #[test]
+    fn keeps_names_on_one_field() {
+        let dir = TempDir::new("selection-log-name");
+        let path = dir.join("selections.log");
+        let config = Config {
+            selection_log: path.to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        let objective: Position = serde_json::from_str(r#"{ "name": "A\tB\nC" }"#).unwrap();
+        append(&config, &objective);
+        let log = fs::read_to_string(&path).unwrap();
+        let fields: Vec<_> = log.trim_end().split('\t').collect();
+        assert_eq!(fields[1..], ["A B C", "-", "-"]);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::config::{
+    parse_axis_order, Activation, Config, Density, GpuPreference, OutputMode, Theme, WindowMode,
+};
+use crate::keys;
+use imgui::{Condition, Ui};
+use regex::Regex;
+use std::path::PathBuf;
+
+/// Draws a combo box over `options` and updates `value` when one is picked
+fn enum_combo<T: Copy + PartialEq>(
+    ui: &Ui,
+    label: &str,
+    value: &mut T,
+    options: &[T],
+    option_label: impl Fn(T) -> &'static str,
+) {
+    let mut index = options.iter().position(|option| option == value).unwrap_or(0);
+    if ui.combo(label, &mut index, options, |option| option_label(*option).into()) {
+        *value = options[index];
+    }
+}
+
+/// Text field for a chord read by `keys::parse_chord`, with a note when it is not one
+fn key_input(ui: &Ui, label: &str, key: &mut String) {
+    ui.input_text(label, key).build();
+    if !key.is_empty() && keys::parse_chord(key).is_none() {
+        ui.text_disabled("Not a key, use a name such as F4 or Ctrl+E");
+    }
+}
+
+/// Slider for a number of decimal places
+fn precision_slider(ui: &Ui, label: &str, precision: &mut usize) {
+    let mut value = *precision as i32;
+    if ui.slider(label, 0, 6, &mut value) {
+        *precision = value.max(0) as usize;
+    }
+}
+
+/// The "Settings" window, which edits a copy of the config until it is applied
+#[derive(Default)]
+pub struct SettingsWindow {
+    pub open: bool,
+    /// The live config the draft was last copied from
+    base: Config,
+    draft: Config,
+    /// Text buffer for `draft.data_dir`
+    data_dir: String,
+    /// Set when "Reset layout" is clicked, cleared by the caller
+    pub reset_layout: bool,
+}
+
+impl SettingsWindow {
+    /// Opens the window with the current config
+    pub fn show(&mut self, config: &Config) {
+        self.open = true;
+        self.base = config.clone();
+        self.set_draft(config.clone());
+    }
+
+    fn set_draft(&mut self, draft: Config) {
+        self.data_dir = draft.data_dir.display().to_string();
+        self.draft = draft;
+    }
+
+    /// Draws the window and returns `config` with the edits when "Apply" is clicked;
+    /// `layout` is when the default position and size apply
+    pub fn draw(&mut self, ui: &Ui, config: &Config, layout: Condition) -> Option<Config> {
+        if !self.open {
+            return None;
+        }
+
+        // Settings changed elsewhere while the window is open, such as the theme by key,
+        // show up in the draft unless they were edited here
+        if *config != self.base {
+            self.draft.data_dir = PathBuf::from(self.data_dir.trim());
+            let draft = config.with_changes(&self.base, &self.draft);
+            self.base = config.clone();
+            self.set_draft(draft);
+        }
+
+        let mut open = self.open;
+        let mut applied = None;
+        ui.window("Settings")
+            .opened(&mut open)
+            .position([60.0, 60.0], layout)
+            .size([380.0, 0.0], layout)
+            .collapsed(false, layout)
+            .build(|| {
+                let draft = &mut self.draft;
+
+                if ui.collapsing_header("Appearance", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    enum_combo(ui, "Theme", &mut draft.theme, &Theme::ALL, Theme::label);
+                    ui.color_edit3("Accent color", &mut draft.accent_color);
+                    ui.text_disabled("Marks search matches, favorites and selected objectives");
+                    ui.slider("Font size", 8.0, 32.0, &mut draft.font_size);
+                    enum_combo(ui, "Density", &mut draft.density, &Density::ALL, Density::label);
+                    ui.slider("Button rounding", 0.0, 12.0, &mut draft.frame_rounding);
+                    ui.slider("Button border", 0.0, 3.0, &mut draft.frame_border);
+                    ui.checkbox("Tree icons", &mut draft.tree_icons);
+                    ui.checkbox("Animate opening and closing", &mut draft.animate_tree);
+                    ui.checkbox("Compact objectives", &mut draft.compact_objectives);
+                    ui.disabled(draft.compact_objectives, || {
+                        let mut columns = draft.objective_columns as i32;
+                        if ui.slider("Objective columns", 1, 6, &mut columns) {
+                            draft.objective_columns = columns.max(1) as usize;
+                        }
+                    });
+                    ui.checkbox("Full-row selection", &mut draft.full_row_select);
+                    ui.disabled(!draft.full_row_select, || {
+                        ui.slider("Row height", 0.0, 60.0, &mut draft.row_height);
+                    });
+                    ui.checkbox("Show usage counts", &mut draft.show_usage_counts);
+                    ui.checkbox("File path tooltips", &mut draft.path_tooltips);
+                    ui.checkbox("Frame time overlay (F3)", &mut draft.show_fps);
+                    key_input(ui, "Next theme key", &mut draft.theme_key);
+                    key_input(ui, "Errors panel key", &mut draft.errors_key);
+                }
+
+                if ui.collapsing_header("Data", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    ui.input_text("Data directory", &mut self.data_dir).build();
+                    ui.same_line();
+                    if ui.button("Browse...") {
+                        let picked = rfd::FileDialog::new()
+                            .set_directory(self.data_dir.trim())
+                            .pick_folder();
+                        if let Some(dir) = picked {
+                            self.data_dir = dir.display().to_string();
+                        }
+                    }
+                    ui.checkbox("Reopen last opened directory", &mut draft.reopen_last_dir);
+                    ui.checkbox("Expand top level on load", &mut draft.expand_top_level);
+                    ui.checkbox("Open one location at a time", &mut draft.accordion);
+                    ui.input_text("Editor command", &mut draft.editor).build();
+                    ui.text_disabled("e.g. code --goto {file}:{line}, empty uses the default app");
+                    let mut max_results = draft.max_search_results as i32;
+                    if ui.input_int("Max search results", &mut max_results).build() {
+                        draft.max_search_results = max_results.max(0) as usize;
+                    }
+                    ui.text_disabled("0 lists every match");
+                    let mut auto_reload = draft.auto_reload_secs.is_some();
+                    if ui.checkbox("Auto-reload", &mut auto_reload) {
+                        draft.auto_reload_secs = auto_reload.then_some(30);
+                    }
+                    if let Some(secs) = &mut draft.auto_reload_secs {
+                        let mut value = *secs as i32;
+                        if ui.input_int("Interval (seconds)", &mut value).build() {
+                            *secs = value.max(1) as u64;
+                        }
+                    }
+                    let mut lazy = draft.lazy_load_files.is_some();
+                    if ui.checkbox("Load large directories lazily", &mut lazy) {
+                        draft.lazy_load_files = lazy.then_some(2000);
+                    }
+                    if let Some(files) = &mut draft.lazy_load_files {
+                        let mut value = *files as i32;
+                        if ui.input_int("From this many files", &mut value).build() {
+                            *files = value.max(1) as usize;
+                        }
+                        ui.text_disabled("Files are parsed when opened, not searched before");
+                    }
+                    ui.text_disabled(format!(
+                        "Checking positions against {} map bounds ([[map_bounds]] in config.toml)",
+                        draft.map_bounds.len()
+                    ));
+                    ui.checkbox(
+                        "Report identical positions across files",
+                        &mut draft.duplicates_across_files,
+                    );
+                    ui.input_text("Map from path", &mut draft.map_path_pattern).build();
+                    match Regex::new(&draft.map_path_pattern) {
+                        Err(e) if !draft.map_path_pattern.is_empty() => {
+                            ui.text_disabled(format!("Not a valid pattern: {}", e))
+                        }
+                        _ => ui.text_disabled("Such as maps/(\\d+)\\.json, read as the map"),
+                    }
+                }
+
+                if ui.collapsing_header("Coordinates", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    let mut per_axis = draft.axis_precision.is_some();
+                    if ui.checkbox("Per-axis precision", &mut per_axis) {
+                        draft.axis_precision =
+                            per_axis.then_some([draft.coordinate_precision; 3]);
+                    }
+                    match &mut draft.axis_precision {
+                        None => {
+                            precision_slider(ui, "Decimal places", &mut draft.coordinate_precision)
+                        }
+                        Some(axes) => {
+                            for (label, precision) in ["X", "Y", "Z"].into_iter().zip(axes) {
+                                precision_slider(ui, label, precision);
+                            }
+                        }
+                    }
+                    ui.input_text("Axis order", &mut draft.axis_order).build();
+                    for order in ["xyz", "xzy", "yxz"] {
+                        ui.same_line();
+                        if ui.small_button(order) {
+                            draft.axis_order = order.to_string();
+                        }
+                    }
+                    if parse_axis_order(&draft.axis_order).is_none() {
+                        ui.text_disabled("Not a valid order, xyz is used: name each axis once");
+                    }
+                    ui.text_disabled(format!(
+                        "Preview: {}",
+                        draft.format_coords([-1234.5678, 42.0, 9876.543])
+                    ));
+                    ui.input_text("Default hint", &mut draft.position_hint).build();
+                    ui.text_disabled("Shown without a hint, e.g. [{x}, {y}, {z}] or [{pos}]");
+                }
+
+                if ui.collapsing_header("Output", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    enum_combo(
+                        ui,
+                        "On selection",
+                        &mut draft.output_mode,
+                        &OutputMode::ALL,
+                        OutputMode::label,
+                    );
+                    ui.input_text("Coordinate separator", &mut draft.coordinate_separator).build();
+                    for (label, separator) in [("Comma", ", "), ("Space", " "), ("Tab", "\t")] {
+                        ui.same_line();
+                        if ui.small_button(label) {
+                            draft.coordinate_separator = separator.to_string();
+                        }
+                    }
+                    ui.text_disabled(format!(
+                        "Copied as: {}",
+                        draft.format_output([-1234.5678, 42.0, 9876.543]).replace('\t', "<tab>")
+                    ));
+                    ui.checkbox("Quiet (no terminal output)", &mut draft.quiet);
+                    ui.input_text("Selections log", &mut draft.selection_log).build();
+                    ui.text_disabled("Appends every selection to this file, off when empty");
+                    let mut rotate = draft.selection_log_max_kb.is_some();
+                    if ui.checkbox("Rotate the log", &mut rotate) {
+                        draft.selection_log_max_kb = rotate.then_some(1024);
+                    }
+                    if let Some(max_kb) = &mut draft.selection_log_max_kb {
+                        let mut value = *max_kb as i32;
+                        if ui.input_int("Max size (KiB)", &mut value).build() {
+                            *max_kb = value.max(1) as u64;
+                        }
+                    }
+                    ui.input_text("Last selection file", &mut draft.selection_file).build();
+                    ui.text_disabled("Replaced with each selection as one line of JSON");
+                    ui.checkbox("Confirm before teleporting", &mut draft.confirm_selection);
+                    enum_combo(
+                        ui,
+                        "Teleport on",
+                        &mut draft.activation,
+                        &Activation::ALL,
+                        Activation::label,
+                    );
+                    ui.input_text("Map URL", &mut draft.map_url).build();
+                    ui.text_disabled("{map} is replaced by the objective's map ID");
+                }
+
+                if ui.collapsing_header("Window", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    enum_combo(
+                        ui,
+                        "Start as",
+                        &mut draft.window_mode,
+                        &WindowMode::ALL,
+                        WindowMode::label,
+                    );
+                    enum_combo(
+                        ui,
+                        "GPU",
+                        &mut draft.gpu,
+                        &GpuPreference::ALL,
+                        GpuPreference::label,
+                    );
+                    ui.checkbox("Show crash dialog", &mut draft.crash_dialog);
+                    ui.text_disabled("The three settings above apply on the next launch");
+                    ui.separator();
+                    let mut min_size = draft.min_window_size.map(|size| size as i32);
+                    if ui.input_int2("Minimum size", &mut min_size).build() {
+                        draft.min_window_size = min_size.map(|size| size.max(0) as u32);
+                    }
+                    ui.checkbox("Remember window layout", &mut draft.save_layout);
+                    ui.checkbox("Redraw continuously", &mut draft.continuous_redraw);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Keeps the frame time overlay live, at the cost of CPU");
+                    }
+                    if ui.button("Reset layout") {
+                        self.reset_layout = true;
+                    }
+                }
+
+                #[cfg(feature = "http-server")]
+                if ui.collapsing_header("HTTP server", imgui::TreeNodeFlags::DEFAULT_OPEN) {
+                    let mut port = i32::from(draft.http_port);
+                    if ui.input_int("Port", &mut port).build() {
+                        draft.http_port = port.clamp(1, i32::from(u16::MAX)) as u16;
+                    }
+                    ui.text_disabled("Listens on 127.0.0.1, the port applies on the next launch");
+                }
+
+                ui.separator();
+                if ui.button("Apply") {
+                    self.draft.data_dir = PathBuf::from(self.data_dir.trim());
+                    self.draft.clamp();
+                    applied = Some(self.draft.clone());
+                }
+                ui.same_line();
+                if ui.button("Reset to defaults") {
+                    // Keeps what the app remembers between sessions rather than settings
+                    let base = &self.base;
+                    self.set_draft(Config {
+                        recent_dirs: base.recent_dirs.clone(),
+                        search_history: base.search_history.clone(),
+                        last_selected: base.last_selected.clone(),
+                        last_launch: base.last_launch,
+                        map_bounds: base.map_bounds.clone(),
+                        ..Config::default()
+                    });
+                }
+            });
+        self.open = open;
+        applied
+    }
+}
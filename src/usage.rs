@@ -0,0 +1,93 @@
+use crate::data::ObjectiveKey;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// Sidecar file the usage counts are persisted to
+const USAGE_PATH: &str = "usage.json";
+
+/// One line of the usage file; JSON object keys must be strings, so keys are stored inline
+#[derive(Serialize, Deserialize)]
+pub struct UsageEntry {
+    #[serde(flatten)]
+    key: ObjectiveKey,
+    count: u32,
+}
+
+/// How often each objective was selected, persisted to `usage.json`
+#[derive(Default)]
+pub struct Usage {
+    counts: BTreeMap<ObjectiveKey, u32>,
+    /// File the counts are saved to; unset for counts that are not persisted
+    path: Option<PathBuf>,
+    /// Counts the changes, so lists sorted by usage notice when they are stale
+    revision: u64,
+}
+
+impl Usage {
+    /// Loads the usage file, starting empty if it is missing or invalid
+    pub fn load() -> Self {
+        let entries: Vec<UsageEntry> = match fs::read_to_string(USAGE_PATH) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("Error parsing {}: {}", USAGE_PATH, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        let counts = entries.into_iter().map(|entry| (entry.key, entry.count)).collect();
+        Usage {
+            counts,
+            path: Some(PathBuf::from(USAGE_PATH)),
+            revision: 0,
+        }
+    }
+
+    /// Writes the usage file
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&self.entries())
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            eprintln!("Error saving {}: {}", path.display(), e);
+        }
+    }
+
+    /// Returns every count, sorted by objective
+    pub fn entries(&self) -> Vec<UsageEntry> {
+        self.counts
+            .iter()
+            .map(|(key, &count)| UsageEntry { key: key.clone(), count })
+            .collect()
+    }
+
+    /// Takes over imported counts, adding them up where both have one, and saves
+    pub fn merge(&mut self, entries: Vec<UsageEntry>, replace: bool) {
+        if replace {
+            self.counts.clear();
+        }
+        for entry in entries {
+            let count = self.counts.entry(entry.key).or_insert(0);
+            *count = count.saturating_add(entry.count);
+        }
+        self.revision += 1;
+        self.save();
+    }
+
+    /// Number of times the objective was selected
+    pub fn count(&self, key: &ObjectiveKey) -> u32 {
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Counts one selection of an objective and saves the change
+    pub fn record(&mut self, key: ObjectiveKey) {
+        *self.counts.entry(key).or_insert(0) += 1;
+        self.revision += 1;
+        self.save();
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+}